@@ -0,0 +1,10 @@
+-- Create user_totp table (one TOTP authenticator per user, secret encrypted at rest)
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id BLOB PRIMARY KEY,
+    secret_encrypted TEXT NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+    last_used_step INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    confirmed_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
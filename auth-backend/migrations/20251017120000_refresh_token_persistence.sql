@@ -0,0 +1,3 @@
+-- Record whether a refresh token was issued with "remember me" so rotation keeps the same lifetime policy.
+-- Tokens issued before this migration were all 7-day tokens, so they are treated as persistent.
+ALTER TABLE refresh_tokens ADD COLUMN persistent BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,10 @@
+-- Refresh tokens issued from one login share a family; rotated tokens are kept as "used"
+-- so that presenting one again can be detected as a replay.
+ALTER TABLE refresh_tokens ADD COLUMN family_id BLOB;
+ALTER TABLE refresh_tokens ADD COLUMN used_at DATETIME;
+
+-- Existing tokens each start their own family
+UPDATE refresh_tokens SET family_id = id WHERE family_id IS NULL;
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family_id ON refresh_tokens(family_id);
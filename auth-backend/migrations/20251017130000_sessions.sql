@@ -0,0 +1,21 @@
+-- Create sessions table. A session is one sign-in on one device; its id is the
+-- family_id shared by every refresh token rotated from that sign-in.
+CREATE TABLE IF NOT EXISTS sessions (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL,
+    device_name TEXT NOT NULL,
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at DATETIME NOT NULL,
+    last_used_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Existing token families become sessions without device context
+INSERT INTO sessions (id, user_id, device_name, created_at, last_used_at)
+SELECT family_id, user_id, 'Unknown device', MIN(created_at), MAX(created_at)
+FROM refresh_tokens
+GROUP BY family_id, user_id;
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions(user_id);
@@ -0,0 +1,14 @@
+-- Remember which access token was issued alongside each refresh token, so revoking
+-- a session can also revoke the access tokens it handed out.
+ALTER TABLE refresh_tokens ADD COLUMN access_jti BLOB;
+
+-- Create revoked_access_tokens table. Rows are only needed until the token would
+-- have expired anyway and are purged after that.
+CREATE TABLE IF NOT EXISTS revoked_access_tokens (
+    jti BLOB PRIMARY KEY,
+    expires_at DATETIME NOT NULL,
+    revoked_at DATETIME NOT NULL
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_revoked_access_tokens_expires_at ON revoked_access_tokens(expires_at);
@@ -0,0 +1,12 @@
+-- Create login_lockouts table. Keyed by the normalized login email rather than the
+-- user id so that unknown addresses lock out exactly like real accounts.
+CREATE TABLE IF NOT EXISTS login_lockouts (
+    email TEXT PRIMARY KEY,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    lockout_count INTEGER NOT NULL DEFAULT 0,
+    locked_until DATETIME,
+    last_failed_at DATETIME NOT NULL
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_login_lockouts_last_failed_at ON login_lockouts(last_failed_at);
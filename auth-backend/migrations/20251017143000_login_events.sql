@@ -0,0 +1,18 @@
+-- Create login_events table. Every login attempt is recorded, including failures
+-- for unknown emails, so user_id is only set when the account could be identified.
+CREATE TABLE IF NOT EXISTS login_events (
+    id BLOB PRIMARY KEY,
+    user_id BLOB,
+    email TEXT,
+    method TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    failure_reason TEXT,
+    ip_address TEXT,
+    user_agent TEXT,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_login_events_created_at ON login_events(created_at);
+CREATE INDEX IF NOT EXISTS idx_login_events_user_id ON login_events(user_id);
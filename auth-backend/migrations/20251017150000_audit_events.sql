@@ -0,0 +1,30 @@
+-- Create audit_events table. Actor and target are deliberately not foreign keys:
+-- events must outlive the accounts they describe, so the actor's email is copied in.
+CREATE TABLE IF NOT EXISTS audit_events (
+    id BLOB PRIMARY KEY,
+    actor_id BLOB,
+    actor_email TEXT,
+    target_id BLOB,
+    action TEXT NOT NULL,
+    ip_address TEXT,
+    metadata TEXT NOT NULL DEFAULT '{}',
+    created_at DATETIME NOT NULL
+);
+
+-- The log is append-only
+CREATE TRIGGER IF NOT EXISTS audit_events_no_update
+BEFORE UPDATE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_events_no_delete
+BEFORE DELETE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append-only');
+END;
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_audit_events_created_at ON audit_events(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_audit_events_actor_id ON audit_events(actor_id);
+CREATE INDEX IF NOT EXISTS idx_audit_events_action ON audit_events(action);
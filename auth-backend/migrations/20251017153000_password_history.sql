@@ -0,0 +1,17 @@
+-- Create password_history table. Holds the hashes of a user's most recent
+-- passwords, including the current one, so that they cannot be reused.
+CREATE TABLE IF NOT EXISTS password_history (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL,
+    password_hash TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Existing users start with their current password
+INSERT INTO password_history (id, user_id, password_hash, created_at)
+SELECT randomblob(16), id, password_hash, updated_at
+FROM users;
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_password_history_user_id ON password_history(user_id, created_at);
@@ -0,0 +1,10 @@
+-- Track password age and admin-forced password changes
+ALTER TABLE users ADD COLUMN password_changed_at DATETIME;
+ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Existing passwords date from their latest history entry, or account creation
+UPDATE users SET password_changed_at = COALESCE(
+    (SELECT MAX(created_at) FROM password_history WHERE password_history.user_id = users.id),
+    created_at
+)
+WHERE password_changed_at IS NULL;
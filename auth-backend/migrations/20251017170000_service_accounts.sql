@@ -0,0 +1,16 @@
+-- Create service_accounts table. Non-human clients that obtain access tokens
+-- with the OAuth2 client credentials grant. Only a SHA-256 digest of the
+-- client secret is stored. Scopes are space separated.
+CREATE TABLE IF NOT EXISTS service_accounts (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    client_id TEXT NOT NULL UNIQUE,
+    client_secret_hash TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    created_by BLOB,
+    last_used_at DATETIME,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
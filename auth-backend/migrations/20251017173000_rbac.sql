@@ -0,0 +1,70 @@
+-- Create roles, permissions and their assignments. A user's permissions are
+-- the union of the permissions of every role they hold. Built-in roles and
+-- permissions are seeded here and cannot be deleted.
+CREATE TABLE IF NOT EXISTS roles (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    built_in BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS permissions (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    built_in BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id BLOB NOT NULL,
+    permission_id BLOB NOT NULL,
+    PRIMARY KEY (role_id, permission_id),
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    FOREIGN KEY (permission_id) REFERENCES permissions(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_roles (
+    user_id BLOB NOT NULL,
+    role_id BLOB NOT NULL,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_role_permissions_permission_id ON role_permissions(permission_id);
+CREATE INDEX IF NOT EXISTS idx_user_roles_role_id ON user_roles(role_id);
+
+-- Seed the built-in permissions checked by the API
+INSERT OR IGNORE INTO permissions (id, name, description, built_in, created_at) VALUES
+    (randomblob(16), 'dashboard:read', 'View dashboard statistics and recent activity', TRUE, datetime('now')),
+    (randomblob(16), 'analytics:read', 'View login analytics', TRUE, datetime('now')),
+    (randomblob(16), 'users:read', 'List users and their roles', TRUE, datetime('now')),
+    (randomblob(16), 'users:manage', 'Sign users out, unlock them and require password changes', TRUE, datetime('now')),
+    (randomblob(16), 'audit:read', 'Query the audit log', TRUE, datetime('now')),
+    (randomblob(16), 'service_accounts:manage', 'Create, rotate and delete service accounts', TRUE, datetime('now')),
+    (randomblob(16), 'roles:manage', 'Manage roles and permissions and assign roles to users', TRUE, datetime('now'));
+
+-- Seed the built-in roles; admin holds every built-in permission
+INSERT OR IGNORE INTO roles (id, name, description, built_in, created_at, updated_at) VALUES
+    (randomblob(16), 'admin', 'Full administrative access', TRUE, datetime('now'), datetime('now')),
+    (randomblob(16), 'user', 'Default role for new accounts', TRUE, datetime('now'), datetime('now'));
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id
+FROM roles, permissions
+WHERE roles.name = 'admin' AND permissions.built_in = TRUE;
+
+-- Carry over the free-form users.role values as role memberships
+INSERT OR IGNORE INTO roles (id, name, built_in, created_at, updated_at)
+SELECT randomblob(16), role, FALSE, datetime('now'), datetime('now')
+FROM (SELECT DISTINCT role FROM users);
+
+INSERT OR IGNORE INTO user_roles (user_id, role_id, created_at)
+SELECT users.id, roles.id, datetime('now')
+FROM users
+JOIN roles ON roles.name = users.role;
@@ -0,0 +1,28 @@
+-- Create organizations and memberships. Users belong to any number of
+-- organizations with a per-organization role: owner, admin or member.
+CREATE TABLE IF NOT EXISTS organizations (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL,
+    slug TEXT NOT NULL UNIQUE,
+    created_by BLOB,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS memberships (
+    organization_id BLOB NOT NULL,
+    user_id BLOB NOT NULL,
+    role TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    PRIMARY KEY (organization_id, user_id),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- The organization a session acts in, carried in its access tokens
+ALTER TABLE sessions ADD COLUMN active_organization_id BLOB REFERENCES organizations(id) ON DELETE SET NULL;
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_memberships_user_id ON memberships(user_id);
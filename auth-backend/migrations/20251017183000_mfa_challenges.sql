@@ -0,0 +1,14 @@
+-- Create mfa_challenges table. Each login that needs a second factor gets a
+-- row keyed by the mfa_token's jti; it is consumed on success or once too
+-- many codes have failed, so a token cannot be replayed or brute-forced.
+CREATE TABLE IF NOT EXISTS mfa_challenges (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_mfa_challenges_expires_at ON mfa_challenges(expires_at);
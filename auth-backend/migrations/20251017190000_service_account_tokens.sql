@@ -0,0 +1,13 @@
+-- Create service_account_tokens table. Records the jti of each client
+-- credentials token until it expires, so deleting the service account can
+-- denylist the tokens it still holds.
+CREATE TABLE IF NOT EXISTS service_account_tokens (
+    jti BLOB PRIMARY KEY,
+    service_account_id BLOB NOT NULL,
+    expires_at DATETIME NOT NULL,
+    FOREIGN KEY (service_account_id) REFERENCES service_accounts(id) ON DELETE CASCADE
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_service_account_tokens_service_account_id ON service_account_tokens(service_account_id);
+CREATE INDEX IF NOT EXISTS idx_service_account_tokens_expires_at ON service_account_tokens(expires_at);
@@ -1,4 +1,4 @@
 pub mod database;
 pub mod settings;
 
-pub use settings::Settings;
\ No newline at end of file
+pub use settings::{CookieSameSite, EmailVerificationPolicy, PasswordHashAlgorithm, RateLimit, Settings};
\ No newline at end of file
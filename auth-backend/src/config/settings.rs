@@ -1,18 +1,182 @@
 use serde::Deserialize;
+use std::str::FromStr;
+
+/// Controls what an account with an unverified email address is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailVerificationPolicy {
+    /// Verification is tracked but never enforced.
+    Optional,
+    /// Unverified users cannot log in and are rejected by `RequireVerifiedEmail` routes.
+    RequiredForLogin,
+    /// Unverified users can log in but are rejected by `RequireVerifiedEmail` routes.
+    RequiredForRoutes,
+}
+
+impl FromStr for EmailVerificationPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "optional" | "off" => Ok(Self::Optional),
+            "login" | "required_for_login" => Ok(Self::RequiredForLogin),
+            "routes" | "required_for_routes" => Ok(Self::RequiredForRoutes),
+            other => Err(anyhow::anyhow!("Unknown email verification policy: {}", other)),
+        }
+    }
+}
+
+/// Algorithm used for newly hashed passwords. Hashes made with the other one
+/// still verify and are upgraded at the next successful login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordHashAlgorithm {
+    Argon2id,
+    Bcrypt,
+}
+
+impl FromStr for PasswordHashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "argon2id" | "argon2" => Ok(Self::Argon2id),
+            "bcrypt" => Ok(Self::Bcrypt),
+            other => Err(anyhow::anyhow!("Unknown password hash algorithm: {}", other)),
+        }
+    }
+}
+
+/// `SameSite` attribute of the session cookies set in `AUTH_COOKIES` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieSameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl FromStr for CookieSameSite {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "lax" => Ok(Self::Lax),
+            "none" => Ok(Self::None),
+            other => Err(anyhow::anyhow!("Unknown SameSite value: {}", other)),
+        }
+    }
+}
+
+/// A request budget of `requests` per `window_seconds`, written as `10/60`.
+/// `off` disables the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window_seconds: u64,
+}
+
+impl RateLimit {
+    pub const fn new(requests: u32, window_seconds: u64) -> Self {
+        Self { requests, window_seconds }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.requests > 0 && self.window_seconds > 0
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("off") {
+            return Ok(Self::new(0, 0));
+        }
+
+        let (requests, window) = value
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Expected <requests>/<seconds>, got {}", value))?;
+
+        Ok(Self::new(requests.trim().parse()?, window.trim().parse()?))
+    }
+}
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     pub database_url: String,
     pub jwt_secret: String,
     pub jwt_refresh_secret: String,
+    pub jwt_signing_key_path: Option<String>,
+    pub jwt_verification_key_paths: Vec<String>,
     pub cors_origin: String,
     pub server_port: u16,
+    /// Reverse proxies in front of the server that append to `X-Forwarded-For`; 0 ignores the header
+    pub trusted_proxy_hops: usize,
+    pub auth_cookies: bool,
+    pub cookie_secure: bool,
+    pub cookie_same_site: CookieSameSite,
+    pub cookie_domain: Option<String>,
+    pub rate_limit_login: RateLimit,
+    pub rate_limit_register: RateLimit,
+    pub rate_limit_refresh: RateLimit,
+    pub rate_limit_mfa: RateLimit,
+    pub rate_limit_email: RateLimit,
+    pub rate_limit_token: RateLimit,
+    pub app_base_url: String,
+    pub refresh_token_session_ttl_hours: i64,
+    pub refresh_token_persistent_ttl_days: i64,
+    pub lockout_threshold: i64,
+    pub lockout_base_minutes: i64,
+    pub lockout_max_minutes: i64,
+    pub password_min_length: usize,
+    pub password_max_length: usize,
+    pub password_require_lowercase: bool,
+    pub password_require_uppercase: bool,
+    pub password_require_digit: bool,
+    pub password_require_symbol: bool,
+    pub password_banned_words: Vec<String>,
+    pub password_history_size: usize,
+    pub password_max_age_days: i64,
+    pub password_hash_algorithm: PasswordHashAlgorithm,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    pub bcrypt_cost: u32,
+    pub hashing_workers: usize,
+    pub hashing_queue_limit: usize,
+    pub metrics_token: Option<String>,
+    pub breached_passwords_path: Option<String>,
+    pub breached_passwords_min_count: u64,
+    pub email_verification: EmailVerificationPolicy,
+    pub email_verification_ttl_hours: i64,
+    pub password_reset_ttl_minutes: i64,
+    pub mfa_issuer: String,
+    pub mfa_encryption_key: String,
+    pub mfa_challenge_ttl_minutes: i64,
+    pub mfa_max_attempts: i64,
+    pub webauthn_rp_id: String,
+    pub webauthn_rp_name: String,
+    pub webauthn_origin: String,
+    pub webauthn_challenge_ttl_minutes: i64,
+    pub webauthn_require_user_verification: bool,
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_outbox_path: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
 }
 
 impl Settings {
     pub fn new() -> anyhow::Result<Self> {
         dotenv::dotenv().ok();
-        
+
+        let cors_origin = std::env::var("CORS_ORIGIN")
+            .unwrap_or_else(|_| "http://localhost:5173".to_string());
+
         let settings = Settings {
             database_url: std::env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:./auth.db".to_string()),
@@ -20,14 +184,141 @@ impl Settings {
                 .unwrap_or_else(|_| "your-super-secure-secret-key".to_string()),
             jwt_refresh_secret: std::env::var("JWT_REFRESH_SECRET")
                 .unwrap_or_else(|_| "your-refresh-secret-key".to_string()),
-            cors_origin: std::env::var("CORS_ORIGIN")
-                .unwrap_or_else(|_| "http://localhost:5173".to_string()),
+            // When set, access tokens are signed with this key instead of JWT_SECRET
+            jwt_signing_key_path: std::env::var("JWT_SIGNING_KEY_PATH").ok(),
+            // Previous signing keys that still verify during a rotation
+            jwt_verification_key_paths: std::env::var("JWT_VERIFICATION_KEY_PATHS")
+                .map(|paths| {
+                    paths
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|path| !path.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            app_base_url: std::env::var("APP_BASE_URL")
+                .unwrap_or_else(|_| cors_origin.clone()),
+            webauthn_origin: std::env::var("WEBAUTHN_ORIGIN")
+                .unwrap_or_else(|_| cors_origin.clone()),
+            cors_origin,
             server_port: std::env::var("SERVER_PORT")
                 .unwrap_or_else(|_| "3001".to_string())
                 .parse()
                 .unwrap_or(3001),
+            // TRUST_PROXY_HEADERS=true is shorthand for a single proxy
+            trusted_proxy_hops: env_parse(
+                "TRUSTED_PROXY_HOPS",
+                usize::from(env_parse("TRUST_PROXY_HEADERS", false)?),
+            )?,
+            // Deliver tokens as HttpOnly cookies instead of in response bodies
+            auth_cookies: env_parse("AUTH_COOKIES", false)?,
+            // Only disable for local development over plain HTTP
+            cookie_secure: env_parse("COOKIE_SECURE", true)?,
+            cookie_same_site: env_parse("COOKIE_SAME_SITE", CookieSameSite::Strict)?,
+            cookie_domain: std::env::var("COOKIE_DOMAIN").ok(),
+            rate_limit_login: env_parse("RATE_LIMIT_LOGIN", RateLimit::new(10, 60))?,
+            rate_limit_register: env_parse("RATE_LIMIT_REGISTER", RateLimit::new(5, 600))?,
+            rate_limit_refresh: env_parse("RATE_LIMIT_REFRESH", RateLimit::new(30, 60))?,
+            rate_limit_mfa: env_parse("RATE_LIMIT_MFA", RateLimit::new(10, 300))?,
+            // Endpoints that send email or consume emailed tokens
+            rate_limit_email: env_parse("RATE_LIMIT_EMAIL", RateLimit::new(5, 600))?,
+            // OAuth2 token endpoint for service accounts
+            rate_limit_token: env_parse("RATE_LIMIT_TOKEN", RateLimit::new(30, 60))?,
+            refresh_token_session_ttl_hours: env_parse("REFRESH_TOKEN_SESSION_TTL_HOURS", 12)?,
+            refresh_token_persistent_ttl_days: env_parse("REFRESH_TOKEN_PERSISTENT_TTL_DAYS", 30)?,
+            lockout_threshold: env_parse("LOCKOUT_THRESHOLD", 5)?,
+            lockout_base_minutes: env_parse("LOCKOUT_BASE_MINUTES", 15)?,
+            lockout_max_minutes: env_parse("LOCKOUT_MAX_MINUTES", 24 * 60)?,
+            password_min_length: env_parse("PASSWORD_MIN_LENGTH", 8)?,
+            password_max_length: env_parse("PASSWORD_MAX_LENGTH", 128)?,
+            password_require_lowercase: env_parse("PASSWORD_REQUIRE_LOWERCASE", true)?,
+            password_require_uppercase: env_parse("PASSWORD_REQUIRE_UPPERCASE", true)?,
+            password_require_digit: env_parse("PASSWORD_REQUIRE_DIGIT", true)?,
+            password_require_symbol: env_parse("PASSWORD_REQUIRE_SYMBOL", false)?,
+            // Matched case-insensitively anywhere in the password; the user's email and name always are
+            password_banned_words: std::env::var("PASSWORD_BANNED_WORDS")
+                .unwrap_or_else(|_| "password,qwerty,letmein,welcome".to_string())
+                .split(',')
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect(),
+            // Number of recent passwords, including the current one, that cannot be reused
+            password_history_size: env_parse("PASSWORD_HISTORY_SIZE", 5)?,
+            // Days before a password expires and must be changed; 0 disables expiry
+            password_max_age_days: env_parse("PASSWORD_MAX_AGE_DAYS", 0)?,
+            password_hash_algorithm: env_parse("PASSWORD_HASH_ALGORITHM", PasswordHashAlgorithm::Argon2id)?,
+            // Defaults follow the OWASP recommendation for Argon2id (19 MiB, 2 passes, 1 lane)
+            argon2_memory_kib: env_parse("ARGON2_MEMORY_KIB", 19 * 1024)?,
+            argon2_iterations: env_parse("ARGON2_ITERATIONS", 2)?,
+            argon2_parallelism: env_parse("ARGON2_PARALLELISM", 1)?,
+            bcrypt_cost: env_parse("BCRYPT_COST", bcrypt::DEFAULT_COST)?,
+            // Hashing gets its own threads so bursts cannot starve the async runtime
+            hashing_workers: env_parse(
+                "HASHING_WORKERS",
+                std::thread::available_parallelism().map_or(2, |n| n.get()),
+            )?,
+            // Jobs waiting beyond this are rejected with 503 instead of piling up
+            hashing_queue_limit: env_parse("HASHING_QUEUE_LIMIT", 64)?,
+            // Bearer token the Prometheus scraper sends; /metrics answers 404 without one
+            metrics_token: std::env::var("METRICS_TOKEN").ok().filter(|token| !token.is_empty()),
+            breached_passwords_path: std::env::var("BREACHED_PASSWORDS_PATH").ok(),
+            // Passwords seen fewer times than this in breaches are still accepted
+            breached_passwords_min_count: env_parse("BREACHED_PASSWORDS_MIN_COUNT", 1)?,
+            email_verification: env_parse("EMAIL_VERIFICATION", EmailVerificationPolicy::Optional)?,
+            email_verification_ttl_hours: env_parse("EMAIL_VERIFICATION_TTL_HOURS", 24)?,
+            password_reset_ttl_minutes: env_parse("PASSWORD_RESET_TTL_MINUTES", 60)?,
+            mfa_issuer: std::env::var("MFA_ISSUER")
+                .unwrap_or_else(|_| "AuthFlow".to_string()),
+            mfa_encryption_key: mfa_encryption_key()?,
+            mfa_challenge_ttl_minutes: env_parse("MFA_CHALLENGE_TTL_MINUTES", 5)?,
+            // Wrong codes accepted per mfa_token before it is discarded
+            mfa_max_attempts: env_parse("MFA_MAX_ATTEMPTS", 5)?,
+            webauthn_rp_id: std::env::var("WEBAUTHN_RP_ID")
+                .unwrap_or_else(|_| "localhost".to_string()),
+            webauthn_rp_name: std::env::var("WEBAUTHN_RP_NAME")
+                .unwrap_or_else(|_| "AuthFlow".to_string()),
+            webauthn_challenge_ttl_minutes: env_parse("WEBAUTHN_CHALLENGE_TTL_MINUTES", 5)?,
+            webauthn_require_user_verification: env_parse("WEBAUTHN_REQUIRE_USER_VERIFICATION", true)?,
+            mail_transport: std::env::var("MAIL_TRANSPORT")
+                .unwrap_or_else(|_| "log".to_string()),
+            mail_from: std::env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "AuthFlow <no-reply@localhost>".to_string()),
+            mail_outbox_path: std::env::var("MAIL_OUTBOX_PATH").ok(),
+            smtp_host: std::env::var("SMTP_HOST").ok(),
+            smtp_port: env_parse("SMTP_PORT", 587)?,
+            smtp_username: std::env::var("SMTP_USERNAME").ok(),
+            smtp_password: std::env::var("SMTP_PASSWORD").ok(),
         };
-        
+
         Ok(settings)
     }
-}
\ No newline at end of file
+}
+
+/// Required, with no fallback: TOTP secrets encrypted under one key cannot be
+/// decrypted with another, so the key must not change along with any other secret.
+fn mfa_encryption_key() -> anyhow::Result<String> {
+    let key = std::env::var("MFA_ENCRYPTION_KEY").map_err(|_| {
+        anyhow::anyhow!("MFA_ENCRYPTION_KEY must be set to 64 hex characters (e.g. `openssl rand -hex 32`)")
+    })?;
+
+    if key.len() != 64 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!("MFA_ENCRYPTION_KEY must be 64 hex characters (32 bytes)"));
+    }
+
+    Ok(key)
+}
+
+/// Reads and parses an optional environment variable, falling back to `default` when unset.
+fn env_parse<T>(key: &str, default: T) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid value for {}: {}", key, e)),
+        Err(_) => Ok(default),
+    }
+}
@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{
+        access_token::CreateAccessTokenRequest,
+        response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
+    },
+    services::access_token_service::AccessTokenService,
+    middleware::auth::{AuthUser, RequireSession},
+    AppState,
+};
+
+/// List personal access tokens
+#[utoipa::path(
+    get,
+    path = "/api/users/tokens",
+    responses(
+        (status = 200, description = "Access tokens retrieved", body = ApiResponse<Vec<AccessToken>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+) -> impl IntoResponse {
+    let access_token_service = AccessTokenService::new(&state.pool);
+
+    match access_token_service.list_tokens(auth_user.user.id).await {
+        Ok(tokens) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(tokens, "Access tokens retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("List access tokens error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Create a personal access token. The token is only shown in this response.
+#[utoipa::path(
+    post,
+    path = "/api/users/tokens",
+    request_body = CreateAccessTokenRequest,
+    responses(
+        (status = 201, description = "Access token created", body = ApiResponse<CreatedAccessToken>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    client: ClientInfo,
+    Json(payload): Json<CreateAccessTokenRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let access_token_service = AccessTokenService::new(&state.pool);
+
+    match access_token_service.create_token(auth_user.user.id, payload, &client).await {
+        Ok(created) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(created, "Access token created. Copy it now, it will not be shown again")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Create access token error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Revoke a personal access token
+#[utoipa::path(
+    delete,
+    path = "/api/users/tokens/{id}",
+    params(("id" = Uuid, Path, description = "Access token ID")),
+    responses(
+        (status = 200, description = "Access token revoked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 404, description = "Access token not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    client: ClientInfo,
+    Path(token_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let access_token_service = AccessTokenService::new(&state.pool);
+
+    match access_token_service.revoke_token(auth_user.user.id, token_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Access token revoked", "Access token revoked successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Revoke access token error: {:?}", e);
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -8,12 +8,22 @@ use axum::{
 #[allow(unused_imports)]
 use crate::{
     models::response::{ApiResponse, ErrorResponse, DashboardStats, ActivityItem},
+    models::audit::{AuditAction, AuditEventPage, AuditQuery},
+    models::session::ClientInfo,
+    models::organization::OrgRole,
+    models::role::permissions::{AuditRead, DashboardRead, UsersManage, UsersRead},
+    services::audit_service::{AuditService, NewAuditEvent},
     services::admin_service::AdminService,
     services::user_service::UserService,
-    middleware::auth::{AuthUser, RequireRole},
+    middleware::auth::{AuthUser, RequirePermission},
     AppState,
     models::user::User,
+    models::session::RevokedSessions,
+    services::session_service::SessionService,
+    services::lockout_service::LockoutService,
 };
+use uuid::Uuid;
+use validator::Validate;
 
 /// Get dashboard statistics
 #[utoipa::path(
@@ -30,7 +40,7 @@ use crate::{
 pub async fn get_dashboard_stats(
     State(state): State<AppState>,
     _auth_user: AuthUser,
-    _require_admin: RequireRole,
+    _permission: RequirePermission<DashboardRead>,
 ) -> impl IntoResponse {
     let admin_service = AdminService::new(&state.pool);
     
@@ -64,7 +74,7 @@ pub async fn get_dashboard_stats(
 pub async fn get_recent_activity(
     State(state): State<AppState>,
     _auth_user: AuthUser,
-    _require_admin: RequireRole,
+    _permission: RequirePermission<DashboardRead>,
 ) -> impl IntoResponse {
     let admin_service = AdminService::new(&state.pool);
     
@@ -87,7 +97,7 @@ pub async fn get_recent_activity(
     get,
     path = "/api/admin/users",
     responses(
-        (status = 200, description = "List all users", body = ApiResponse<Vec<User>>),
+        (status = 200, description = "All users with `users:read`; for organization admins, members of their active organization", body = ApiResponse<Vec<User>>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Forbidden", body = ErrorResponse)
     ),
@@ -96,11 +106,18 @@ pub async fn get_recent_activity(
 )]
 pub async fn list_users(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
-    _require_admin: RequireRole,
+    auth_user: AuthUser,
 ) -> impl IntoResponse {
-    let user_service = UserService::new(&state.pool);
-    match user_service.list_users().await {
+    let organization_id = match auth_user.require_permission::<UsersRead>() {
+        Ok(()) => None,
+        Err(rejection) => match &auth_user.membership {
+            Some(membership) if membership.role >= OrgRole::Admin => Some(membership.organization_id),
+            _ => return rejection,
+        },
+    };
+
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
+    match user_service.list_users(organization_id).await {
         Ok(users) => (
             StatusCode::OK,
             Json(ApiResponse::success(users, "User list retrieved")),
@@ -110,4 +127,226 @@ pub async fn list_users(
             Json(ErrorResponse::new(e.to_string())),
         ).into_response(),
     }
-}
\ No newline at end of file
+}
+
+/// Sign a user out everywhere
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/revoke-sessions",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User sessions and access tokens revoked", body = ApiResponse<RevokedSessions>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<UsersManage>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let session_service = SessionService::new(&state.pool, &state.revoked_tokens);
+    match session_service.revoke_all_sessions(user_id).await {
+        Ok(revoked) => {
+            record_admin_action(
+                &state,
+                AuditAction::AdminRevokeSessions,
+                &auth_user,
+                user_id,
+                &client,
+                serde_json::json!({ "revoked": revoked }),
+            )
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success(RevokedSessions { revoked }, "User signed out everywhere")),
+            ).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Revoke user sessions error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Clear a user's login lockout
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/unlock",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User unlocked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<UsersManage>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let lockout_service = LockoutService::new(&state.pool, &state.settings);
+    match lockout_service.unlock_user(user_id).await {
+        Ok(true) => {
+            record_admin_action(
+                &state,
+                AuditAction::AdminUnlockUser,
+                &auth_user,
+                user_id,
+                &client,
+                serde_json::json!({}),
+            )
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success("User unlocked", "User unlocked successfully")),
+            ).into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("User not found")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Unlock user error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Require a user to change their password
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/require-password-change",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User must change their password before doing anything else", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn require_password_change(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<UsersManage>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
+    match user_service.require_password_change(user_id).await {
+        Ok(true) => {
+            record_admin_action(
+                &state,
+                AuditAction::AdminRequirePasswordChange,
+                &auth_user,
+                user_id,
+                &client,
+                serde_json::json!({}),
+            )
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success("Password change required", "User must change their password")),
+            ).into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("User not found")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Require password change error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Query the audit log
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit events, newest first", body = ApiResponse<AuditEventPage>),
+        (status = 400, description = "Invalid filter or cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    _permission: RequirePermission<AuditRead>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    if let Err(errors) = query.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let audit_service = AuditService::new(&state.pool);
+    match audit_service.list_events(&query).await {
+        Ok(page) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(page, "Audit events retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Audit log query error: {:?}", e);
+            let status = if e.to_string().contains("Invalid cursor") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// The admin action has already happened, so a failed audit write is logged rather than returned.
+async fn record_admin_action(
+    state: &AppState,
+    action: AuditAction,
+    admin: &AuthUser,
+    target_id: Uuid,
+    client: &ClientInfo,
+    metadata: serde_json::Value,
+) {
+    let event = NewAuditEvent {
+        action,
+        actor_id: Some(admin.user.id),
+        target_id: Some(target_id),
+        client,
+        metadata,
+    };
+
+    if let Err(e) = AuditService::new(&state.pool).record(event).await {
+        tracing::error!("Audit event error: {:?}", e);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use crate::{services::admin_service::AdminService, models::response::{ApiResponse, ErrorResponse}, AppState, middleware::auth::{AuthUser, RequireRole}};
+use crate::{services::admin_service::AdminService, models::response::{ApiResponse, ErrorResponse}, AppState, middleware::auth::{AuthUser, RequirePermission}, models::role::permissions::AnalyticsRead};
 
 #[utoipa::path(
     get,
@@ -15,7 +15,7 @@ use crate::{services::admin_service::AdminService, models::response::{ApiRespons
 pub async fn logins_per_day(
     State(state): State<AppState>,
     _auth_user: AuthUser,
-    _require_admin: RequireRole,
+    _permission: RequirePermission<AnalyticsRead>,
 ) -> impl IntoResponse {
     let admin_service = AdminService::new(&state.pool);
     match admin_service.get_logins_per_day().await {
@@ -1,18 +1,31 @@
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{header, HeaderMap, Method, StatusCode},
     response::IntoResponse,
     Json,
 };
+use axum_extra::extract::CookieJar;
 use validator::Validate;
 
 use crate::{
+    handlers::hashing_pool_saturated,
     models::{
-        auth::{RegisterRequest, LoginRequest, RefreshRequest},
+        auth::{RegisterRequest, LoginRequest, LoginResponse, RefreshRequest, VerifyEmailRequest, ResendVerificationRequest, ForgotPasswordRequest, ResetPasswordRequest},
+        organization::SwitchOrganizationRequest,
         response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
+    },
+    services::{
+        auth_service::AuthService,
+        lockout_service::AccountLocked,
+        password_reset_service::PasswordResetService,
+        verification_service::VerificationService,
+    },
+    middleware::auth::{AuthUser, PasswordChangeUser, RequireSession},
+    utils::{
+        cookies::{csrf_token_matches, SessionCookies, REFRESH_TOKEN_COOKIE},
+        validation::{with_password_violations, PasswordPolicy, PasswordPolicyViolation},
     },
-    services::auth_service::AuthService,
-    middleware::auth::AuthUser,
     AppState,
 };
 
@@ -24,16 +37,21 @@ use crate::{
     responses(
         (status = 201, description = "User registered successfully", body = ApiResponse<AuthResponse>),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 409, description = "User already exists", body = ErrorResponse)
+        (status = 409, description = "User already exists", body = ErrorResponse),
+        (status = 503, description = "Password hashing queue is full (code `server_busy`)", body = ErrorResponse)
     ),
     tag = "auth"
 )]
 pub async fn register(
     State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
     Json(payload): Json<RegisterRequest>,
 ) -> impl IntoResponse {
     // Validate request
-    if let Err(errors) = payload.validate() {
+    let mut violations = PasswordPolicy::from_settings(&state.settings).check(&payload.password, &[&payload.email]);
+    violations.extend(state.breached_passwords.check(&payload.password));
+    if let Err(errors) = with_password_violations(payload.validate(), "password", violations) {
         let error_details = serde_json::to_value(&errors).unwrap_or_default();
         return (
             StatusCode::BAD_REQUEST,
@@ -41,14 +59,26 @@ pub async fn register(
         ).into_response();
     }
 
-    let auth_service = AuthService::new(&state.pool, &state.jwt_keys);
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
     
-    match auth_service.register(payload).await {
-        Ok(auth_response) => (
-            StatusCode::CREATED,
-            Json(ApiResponse::success(auth_response, "User registered successfully")),
-        ).into_response(),
+    match auth_service.register(payload, &client).await {
+        Ok(auth_response) => {
+            let verification_service = VerificationService::new(&state.pool, &state.mailer, &state.settings);
+            if let Err(e) = verification_service.send_verification(&auth_response.user).await {
+                tracing::error!("Verification email error: {:?}", e);
+            }
+
+            let (jar, auth_response) = SessionCookies::new(&state.settings).issue(jar, auth_response);
+            (
+                StatusCode::CREATED,
+                jar,
+                Json(ApiResponse::success(auth_response, "User registered successfully")),
+            ).into_response()
+        }
         Err(e) => {
+            if let Some(response) = hashing_pool_saturated(&e) {
+                return response;
+            }
             tracing::error!("Registration error: {:?}", e);
             let status = if e.to_string().contains("already exists") {
                 StatusCode::CONFLICT
@@ -66,14 +96,19 @@ pub async fn register(
     path = "/api/auth/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = ApiResponse<AuthResponse>),
+        (status = 200, description = "Login successful, or a second factor is required", body = ApiResponse<LoginResponse>),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 401, description = "Invalid credentials", body = ErrorResponse)
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 403, description = "Email not verified", body = ErrorResponse),
+        (status = 423, description = "Too many failed attempts (code `account_locked`)", body = ErrorResponse),
+        (status = 503, description = "Password hashing queue is full (code `server_busy`)", body = ErrorResponse)
     ),
     tag = "auth"
 )]
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
     Json(payload): Json<LoginRequest>,
 ) -> impl IntoResponse {
     // Validate request
@@ -85,17 +120,53 @@ pub async fn login(
         ).into_response();
     }
 
-    let auth_service = AuthService::new(&state.pool, &state.jwt_keys);
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
     
-    match auth_service.login(payload).await {
-        Ok(auth_response) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(auth_response, "Login successful")),
-        ).into_response(),
+    match auth_service.login(payload, &client).await {
+        Ok(login_response) => {
+            let message = match &login_response {
+                LoginResponse::Authenticated(auth) if auth.password_change_required.is_some() => {
+                    "Login successful. Please change your password"
+                }
+                LoginResponse::Authenticated(_) => "Login successful",
+                LoginResponse::MfaRequired(_) => "Two-factor authentication required",
+            };
+            // Cookies are only issued once no further factor is needed
+            let (jar, login_response) = match login_response {
+                LoginResponse::Authenticated(auth_response) => {
+                    let (jar, auth_response) = SessionCookies::new(&state.settings).issue(jar, auth_response);
+                    (jar, LoginResponse::Authenticated(auth_response))
+                }
+                challenge => (jar, challenge),
+            };
+            (
+                StatusCode::OK,
+                jar,
+                Json(ApiResponse::success(login_response, message)),
+            ).into_response()
+        }
         Err(e) => {
+            if let Some(response) = hashing_pool_saturated(&e) {
+                return response;
+            }
+            if let Some(locked) = e.downcast_ref::<AccountLocked>() {
+                let retry_after = locked.retry_after_seconds();
+                return (
+                    StatusCode::LOCKED,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    Json(ErrorResponse::with_code(
+                        "account_locked",
+                        locked.to_string(),
+                        Some(serde_json::json!({ "retry_after": retry_after })),
+                    )),
+                ).into_response();
+            }
+
             tracing::error!("Login error: {:?}", e);
             let status = if e.to_string().contains("Invalid credentials") {
                 StatusCode::UNAUTHORIZED
+            } else if e.to_string().contains("Email not verified") {
+                StatusCode::FORBIDDEN
             } else {
                 StatusCode::INTERNAL_SERVER_ERROR
             };
@@ -110,20 +181,26 @@ pub async fn login(
     path = "/api/auth/logout",
     responses(
         (status = 200, description = "Logout successful", body = ApiResponse<String>),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "auth"
 )]
 pub async fn logout(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    // Users who must change their password can still sign out
+    PasswordChangeUser(auth_user): PasswordChangeUser,
+    RequireSession(session_id): RequireSession,
+    client: ClientInfo,
+    jar: CookieJar,
 ) -> impl IntoResponse {
-    let auth_service = AuthService::new(&state.pool, &state.jwt_keys);
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
     
-    match auth_service.logout(auth_user.user.id).await {
+    match auth_service.logout(auth_user.user.id, session_id, &client).await {
         Ok(_) => (
             StatusCode::OK,
+            SessionCookies::new(&state.settings).clear(jar),
             Json(ApiResponse::success("Logged out", "Logout successful")),
         ).into_response(),
         Err(e) => {
@@ -149,25 +226,234 @@ pub async fn logout(
 )]
 pub async fn refresh_token(
     State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    headers: HeaderMap,
     Json(payload): Json<RefreshRequest>,
 ) -> impl IntoResponse {
-    let auth_service = AuthService::new(&state.pool, &state.jwt_keys);
+    let cookies = SessionCookies::new(&state.settings);
+
+    // Without a token in the body, cookie mode falls back to the refresh token cookie
+    let refresh_token = match jar.get(REFRESH_TOKEN_COOKIE) {
+        Some(cookie) if cookies.enabled() && payload.refresh_token.is_empty() => {
+            if !csrf_token_matches(&Method::POST, &headers, &jar) {
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(ErrorResponse::with_code("csrf_token_invalid", "Missing or invalid CSRF token", None)),
+                ).into_response();
+            }
+            cookie.value().to_string()
+        }
+        _ => payload.refresh_token,
+    };
+
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
     
-    match auth_service.refresh_token(payload.refresh_token).await {
-        Ok(auth_response) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(auth_response, "Token refreshed successfully")),
-        ).into_response(),
+    match auth_service.refresh_token(refresh_token, &client).await {
+        Ok(auth_response) => {
+            let (jar, auth_response) = cookies.issue(jar, auth_response);
+            (
+                StatusCode::OK,
+                jar,
+                Json(ApiResponse::success(auth_response, "Token refreshed successfully")),
+            ).into_response()
+        }
         Err(e) => {
             tracing::error!("Token refresh error: {:?}", e);
             (
                 StatusCode::UNAUTHORIZED,
+                cookies.clear(jar),
                 Json(ErrorResponse::new("Invalid refresh token")),
             ).into_response()
         }
     }
 }
 
+/// Verify email address
+#[utoipa::path(
+    post,
+    path = "/api/auth/verify-email",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 200, description = "Email verified successfully", body = ApiResponse<User>),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let verification_service = VerificationService::new(&state.pool, &state.mailer, &state.settings);
+
+    match verification_service.verify_email(&payload.token).await {
+        Ok(user) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(user, "Email verified successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Email verification error: {:?}", e);
+            let status = if e.to_string().contains("Invalid or expired") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// Resend verification email
+#[utoipa::path(
+    post,
+    path = "/api/auth/resend-verification",
+    request_body = ResendVerificationRequest,
+    responses(
+        (status = 200, description = "Verification email sent if the account exists", body = ApiResponse<String>),
+        (status = 400, description = "Validation error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let verification_service = VerificationService::new(&state.pool, &state.mailer, &state.settings);
+
+    match verification_service.resend_verification(&payload.email).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(
+                "Verification email sent",
+                "If the account exists and is unverified, a verification email has been sent",
+            )),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Resend verification error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("Failed to send verification email")),
+            ).into_response()
+        }
+    }
+}
+
+/// Request a password reset email
+#[utoipa::path(
+    post,
+    path = "/api/auth/forgot-password",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 200, description = "Reset email sent if the account exists", body = ApiResponse<String>),
+        (status = 400, description = "Validation error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let reset_service = PasswordResetService::new(&state.pool, &state.mailer, &state.settings, &state.revoked_tokens, &state.hashing_pool);
+
+    match reset_service.request_reset(&payload.email).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(
+                "Reset email sent",
+                "If an account exists for this email, a password reset link has been sent",
+            )),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Forgot password error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("Failed to send password reset email")),
+            ).into_response()
+        }
+    }
+}
+
+/// Reset password using an emailed token
+#[utoipa::path(
+    post,
+    path = "/api/auth/reset-password",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset successfully", body = ApiResponse<String>),
+        (status = 400, description = "Validation error, password policy violation or invalid token", body = ErrorResponse),
+        (status = 503, description = "Password hashing queue is full (code `server_busy`)", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> impl IntoResponse {
+    // The remaining policy rules need the user, which only the token identifies
+    let violations = state.breached_passwords.check(&payload.new_password).into_iter().collect();
+    if let Err(errors) = with_password_violations(payload.validate(), "new_password", violations) {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let reset_service = PasswordResetService::new(&state.pool, &state.mailer, &state.settings, &state.revoked_tokens, &state.hashing_pool);
+
+    match reset_service.reset_password(payload, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Password reset", "Password reset successfully")),
+        ).into_response(),
+        Err(e) => {
+            if let Some(response) = hashing_pool_saturated(&e) {
+                return response;
+            }
+            if let Some(PasswordPolicyViolation(errors)) = e.downcast_ref::<PasswordPolicyViolation>() {
+                let error_details = serde_json::to_value(errors).unwrap_or_default();
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::with_details("Validation failed", error_details)),
+                ).into_response();
+            }
+
+            tracing::error!("Password reset error: {:?}", e);
+            let status = if e.to_string().contains("Invalid or expired") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
 /// Get current user
 #[utoipa::path(
     get,
@@ -184,4 +470,70 @@ pub async fn get_current_user(auth_user: AuthUser) -> impl IntoResponse {
         StatusCode::OK,
         Json(ApiResponse::success(auth_user.user, "Current user retrieved")),
     )
-}
\ No newline at end of file
+}
+
+/// Switch the current session to another of your organizations
+///
+/// The session's previous tokens stop working; use the returned pair from now on.
+#[utoipa::path(
+    post,
+    path = "/api/auth/switch-org",
+    request_body = SwitchOrganizationRequest,
+    responses(
+        (status = 200, description = "Organization switched", body = ApiResponse<AuthResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not available to personal access tokens (code `session_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "auth"
+)]
+pub async fn switch_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    RequireSession(session_id): RequireSession,
+    jar: CookieJar,
+    Json(payload): Json<SwitchOrganizationRequest>,
+) -> impl IntoResponse {
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
+
+    match auth_service.switch_organization(auth_user.user, session_id, payload.organization_id).await {
+        Ok(auth_response) => {
+            let (jar, auth_response) = SessionCookies::new(&state.settings).issue(jar, auth_response);
+            (
+                StatusCode::OK,
+                jar,
+                Json(ApiResponse::success(auth_response, "Organization switched")),
+            ).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Switch organization error: {:?}", e);
+            let message = e.to_string();
+            let status = if message.contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if message.contains("expired") {
+                StatusCode::UNAUTHORIZED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(message))).into_response()
+        }
+    }
+}
+
+/// Public keys for verifying access tokens
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = JsonWebKeySet)
+    ),
+    tag = "auth"
+)]
+pub async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    // Verifiers cache this; a short max-age lets rotations propagate quickly
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.jwt_keys.jwks()),
+    )
+}
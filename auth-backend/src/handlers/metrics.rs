@@ -0,0 +1,44 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{models::response::ErrorResponse, utils::password::secrets_match, AppState};
+
+/// Prometheus metrics
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Password hashing pool metrics in Prometheus text format", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or wrong `METRICS_TOKEN` bearer token", body = ErrorResponse),
+        (status = 404, description = "`METRICS_TOKEN` is not configured", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "metrics"
+)]
+pub async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    // Not exposed at all until a scrape token is configured
+    let Some(expected) = &state.settings.metrics_token else {
+        return (StatusCode::NOT_FOUND, Json(ErrorResponse::new("Not found"))).into_response();
+    };
+
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| secrets_match(token, expected)) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorResponse::new("Invalid or missing metrics token")),
+        ).into_response();
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.hashing_pool.render_metrics(),
+    ).into_response()
+}
@@ -0,0 +1,306 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use validator::Validate;
+
+use crate::{
+    models::{
+        mfa::{MfaCodeRequest, MfaVerifyRequest, TotpCodeRequest},
+        response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
+    },
+    services::{auth_service::AuthService, lockout_service::AccountLocked, mfa_service::MfaService},
+    middleware::auth::{AuthUser, RequireSession},
+    utils::cookies::SessionCookies,
+    AppState,
+};
+
+/// Complete login with a second factor
+#[utoipa::path(
+    post,
+    path = "/api/auth/mfa/verify",
+    request_body = MfaVerifyRequest,
+    responses(
+        (status = 200, description = "Login successful", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Invalid MFA token or code", body = ErrorResponse),
+        (status = 423, description = "Too many wrong codes (code `account_locked`)", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn verify_mfa(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(payload): Json<MfaVerifyRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
+
+    match auth_service.verify_mfa(payload, &client).await {
+        Ok(auth_response) => {
+            let (jar, auth_response) = SessionCookies::new(&state.settings).issue(jar, auth_response);
+            (
+                StatusCode::OK,
+                jar,
+                Json(ApiResponse::success(auth_response, "Login successful")),
+            ).into_response()
+        }
+        Err(e) => {
+            if let Some(locked) = e.downcast_ref::<AccountLocked>() {
+                let retry_after = locked.retry_after_seconds();
+                return (
+                    StatusCode::LOCKED,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    Json(ErrorResponse::with_code(
+                        "account_locked",
+                        locked.to_string(),
+                        Some(serde_json::json!({ "retry_after": retry_after })),
+                    )),
+                ).into_response();
+            }
+
+            tracing::error!("MFA verification error: {:?}", e);
+            let message = e.to_string();
+            let status = if message.contains("Invalid") || message.contains("not enabled") {
+                StatusCode::UNAUTHORIZED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(message))).into_response()
+        }
+    }
+}
+
+/// Start TOTP enrollment
+#[utoipa::path(
+    post,
+    path = "/api/users/mfa/totp/enroll",
+    responses(
+        (status = 200, description = "TOTP secret generated", body = ApiResponse<TotpEnrollment>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 409, description = "Two-factor authentication already enabled", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn enroll_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+) -> impl IntoResponse {
+    let mfa_service = MfaService::new(&state.pool, &state.settings);
+
+    match mfa_service.enroll_totp(&auth_user.user).await {
+        Ok(enrollment) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(enrollment, "Scan the code with your authenticator app, then confirm it")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("TOTP enrollment error: {:?}", e);
+            let status = if e.to_string().contains("already enabled") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// Confirm TOTP enrollment
+#[utoipa::path(
+    post,
+    path = "/api/users/mfa/totp/confirm",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled", body = ApiResponse<RecoveryCodes>),
+        (status = 400, description = "Invalid code or no pending enrollment", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn confirm_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Json(payload): Json<TotpCodeRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let mfa_service = MfaService::new(&state.pool, &state.settings);
+
+    match mfa_service.confirm_totp(&auth_user.user, &payload.code).await {
+        Ok(recovery_codes) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(
+                recovery_codes,
+                "Two-factor authentication enabled. Store these recovery codes somewhere safe",
+            )),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("TOTP confirmation error: {:?}", e);
+            let message = e.to_string();
+            let status = if message.contains("Invalid") || message.contains("No pending") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(message))).into_response()
+        }
+    }
+}
+
+/// Disable TOTP
+#[utoipa::path(
+    post,
+    path = "/api/users/mfa/totp/disable",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication disabled", body = ApiResponse<String>),
+        (status = 400, description = "Invalid code or two-factor not enabled", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Json(payload): Json<MfaCodeRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let mfa_service = MfaService::new(&state.pool, &state.settings);
+
+    match mfa_service.disable_totp(&auth_user.user, &payload.code).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Two-factor disabled", "Two-factor authentication disabled")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("TOTP disable error: {:?}", e);
+            let message = e.to_string();
+            let status = if message.contains("Invalid") || message.contains("not enabled") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(message))).into_response()
+        }
+    }
+}
+
+
+/// Get recovery code usage
+#[utoipa::path(
+    get,
+    path = "/api/users/mfa/recovery-codes",
+    responses(
+        (status = 200, description = "Recovery code status retrieved", body = ApiResponse<RecoveryCodeStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn get_recovery_code_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+) -> impl IntoResponse {
+    let mfa_service = MfaService::new(&state.pool, &state.settings);
+
+    match mfa_service.recovery_code_status(auth_user.user.id).await {
+        Ok(status) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(status, "Recovery code status retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Recovery code status error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Regenerate recovery codes
+#[utoipa::path(
+    post,
+    path = "/api/users/mfa/recovery-codes",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes generated", body = ApiResponse<RecoveryCodes>),
+        (status = 400, description = "Invalid code or two-factor not enabled", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Json(payload): Json<MfaCodeRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let mfa_service = MfaService::new(&state.pool, &state.settings);
+
+    match mfa_service.regenerate_recovery_codes(&auth_user.user, &payload.code).await {
+        Ok(recovery_codes) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(
+                recovery_codes,
+                "New recovery codes generated. Previous codes no longer work",
+            )),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Recovery code regeneration error: {:?}", e);
+            let message = e.to_string();
+            let status = if message.contains("Invalid") || message.contains("not enabled") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(message))).into_response()
+        }
+    }
+}
\ No newline at end of file
@@ -1,4 +1,41 @@
+pub mod access_token;
 pub mod auth;
 pub mod user;
 pub mod admin;
-pub mod analytics;
\ No newline at end of file
+pub mod analytics;
+pub mod mfa;
+pub mod metrics;
+pub mod oauth;
+pub mod organization;
+pub mod passkey;
+pub mod role;
+pub mod service_account;
+pub mod session;
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::{models::response::ErrorResponse, services::hashing_pool::HashingPoolSaturated};
+
+/// 503 with `Retry-After` when a password could not be hashed because the
+/// hashing queue is full. Handlers that hash passwords check this first.
+pub(crate) fn hashing_pool_saturated(error: &anyhow::Error) -> Option<Response> {
+    let saturated = error.downcast_ref::<HashingPoolSaturated>()?;
+    let retry_after = saturated.retry_after_seconds;
+
+    Some(
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(ErrorResponse::with_code(
+                "server_busy",
+                saturated.to_string(),
+                Some(serde_json::json!({ "retry_after": retry_after })),
+            )),
+        )
+            .into_response(),
+    )
+}
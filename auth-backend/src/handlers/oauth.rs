@@ -0,0 +1,145 @@
+use axum::{
+    extract::{rejection::FormRejection, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use uuid::Uuid;
+
+use crate::{
+    models::service_account::{OAuthError, TokenRequest, TokenResponse, GRANT_TYPE_CLIENT_CREDENTIALS},
+    services::service_account_service::{granted_scope, ClientCredentialsError, ServiceAccountService},
+    utils::jwt::ACCESS_TOKEN_TTL_MINUTES,
+    AppState,
+};
+
+/// OAuth2 token endpoint. Only the client credentials grant is supported; the
+/// client authenticates with HTTP Basic or `client_id` and `client_secret` in the body.
+#[utoipa::path(
+    post,
+    path = "/oauth/token",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Access token issued", body = TokenResponse),
+        (status = 400, description = "`invalid_request`, `invalid_scope` or `unsupported_grant_type`", body = OAuthError),
+        (status = 401, description = "`invalid_client`", body = OAuthError)
+    ),
+    tag = "oauth"
+)]
+pub async fn token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Form<TokenRequest>, FormRejection>,
+) -> impl IntoResponse {
+    let Ok(Form(payload)) = payload else {
+        return oauth_error(StatusCode::BAD_REQUEST, "invalid_request", "Malformed token request");
+    };
+
+    if payload.grant_type != GRANT_TYPE_CLIENT_CREDENTIALS {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "Only the client_credentials grant is supported",
+        );
+    }
+
+    let basic_credentials = match basic_credentials(&headers) {
+        Ok(credentials) => credentials,
+        Err(response) => return response,
+    };
+    // RFC 6749 section 2.3: a client must not use more than one authentication method
+    let (client_id, client_secret) = match (basic_credentials, payload.client_id, payload.client_secret) {
+        (Some(credentials), None, None) => credentials,
+        (None, Some(client_id), Some(client_secret)) => (client_id, client_secret),
+        (None, None, None) => return invalid_client("Client authentication is required"),
+        _ => {
+            return oauth_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "Authenticate with either HTTP Basic or client_id and client_secret",
+            )
+        }
+    };
+
+    let service_account_service = ServiceAccountService::new(&state.pool, &state.revoked_tokens);
+    let service_account = match service_account_service.authenticate(&client_id, &client_secret).await {
+        Ok(service_account) => service_account,
+        Err(e) => {
+            if e.downcast_ref::<ClientCredentialsError>().is_some() {
+                tracing::warn!(client_id = %client_id, "Client credentials rejected");
+                return invalid_client(&e.to_string());
+            }
+            tracing::error!("Client authentication error: {:?}", e);
+            return oauth_error(StatusCode::INTERNAL_SERVER_ERROR, "server_error", "Internal server error");
+        }
+    };
+
+    let scope = match granted_scope(&service_account, payload.scope.as_deref()) {
+        Ok(scope) => scope,
+        Err(e) => return oauth_error(StatusCode::BAD_REQUEST, "invalid_scope", &e.to_string()),
+    };
+
+    let token_id = Uuid::new_v4();
+    if let Err(e) = service_account_service.record_token(service_account.id, token_id).await {
+        tracing::error!("Client token record error: {:?}", e);
+        return oauth_error(StatusCode::INTERNAL_SERVER_ERROR, "server_error", "Internal server error");
+    }
+
+    match state.jwt_keys.generate_client_token(&service_account, &scope, token_id) {
+        Ok(access_token) => (
+            StatusCode::OK,
+            no_store_headers(),
+            Json(TokenResponse {
+                access_token,
+                token_type: "Bearer".to_string(),
+                expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+                scope,
+            }),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Client token signing error: {:?}", e);
+            oauth_error(StatusCode::INTERNAL_SERVER_ERROR, "server_error", "Internal server error")
+        }
+    }
+}
+
+/// Client id and secret from an `Authorization: Basic` header, if one is sent.
+// Errors are the handler's response
+#[allow(clippy::result_large_err)]
+fn basic_credentials(headers: &HeaderMap) -> Result<Option<(String, String)>, Response> {
+    let Some(value) = headers.get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|decoded| {
+            decoded
+                .split_once(':')
+                .map(|(client_id, client_secret)| Some((client_id.to_string(), client_secret.to_string())))
+        })
+        .ok_or_else(|| invalid_client("Invalid authorization header format"))
+}
+
+/// Token responses must not be cached (RFC 6749 section 5.1).
+fn no_store_headers() -> [(header::HeaderName, &'static str); 2] {
+    [(header::CACHE_CONTROL, "no-store"), (header::PRAGMA, "no-cache")]
+}
+
+fn invalid_client(description: &str) -> Response {
+    let mut response = oauth_error(StatusCode::UNAUTHORIZED, "invalid_client", description);
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        header::HeaderValue::from_static("Basic realm=\"oauth\""),
+    );
+    response
+}
+
+fn oauth_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (status, no_store_headers(), Json(OAuthError::new(error, description))).into_response()
+}
@@ -0,0 +1,422 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{
+        organization::{
+            AddMemberRequest, CreateOrganizationRequest, Membership, OrgRole, UpdateMemberRequest,
+            UpdateOrganizationRequest,
+        },
+        response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
+    },
+    services::organization_service::OrganizationService,
+    middleware::auth::AuthUser,
+    AppState,
+};
+
+/// List the organizations you belong to
+#[utoipa::path(
+    get,
+    path = "/api/organizations",
+    responses(
+        (status = 200, description = "Organizations retrieved", body = ApiResponse<Vec<UserOrganization>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn list_organizations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> impl IntoResponse {
+    let organization_service = OrganizationService::new(&state.pool);
+    let active_organization_id = auth_user.membership.as_ref().map(|membership| membership.organization_id);
+
+    match organization_service.list_user_organizations(auth_user.user.id, active_organization_id).await {
+        Ok(organizations) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(organizations, "Organizations retrieved")),
+        ).into_response(),
+        Err(e) => organization_error("List organizations", e),
+    }
+}
+
+/// Create an organization. You become its owner.
+#[utoipa::path(
+    post,
+    path = "/api/organizations",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Organization created", body = ApiResponse<Organization>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "Slug already taken", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn create_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let organization_service = OrganizationService::new(&state.pool);
+
+    match organization_service.create_organization(auth_user.user.id, payload, &client).await {
+        Ok(organization) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(organization, "Organization created")),
+        ).into_response(),
+        Err(e) => organization_error("Create organization", e),
+    }
+}
+
+/// Get an organization you belong to
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{id}",
+    params(("id" = Uuid, Path, description = "Organization ID")),
+    responses(
+        (status = 200, description = "Organization retrieved", body = ApiResponse<Organization>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn get_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(organization_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, OrgRole::Member).await {
+        return rejection;
+    }
+
+    match OrganizationService::new(&state.pool).get_organization(organization_id).await {
+        Ok(organization) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(organization, "Organization retrieved")),
+        ).into_response(),
+        Err(e) => organization_error("Get organization", e),
+    }
+}
+
+/// Rename an organization (organization admins)
+#[utoipa::path(
+    put,
+    path = "/api/organizations/{id}",
+    params(("id" = Uuid, Path, description = "Organization ID")),
+    request_body = UpdateOrganizationRequest,
+    responses(
+        (status = 200, description = "Organization updated", body = ApiResponse<Organization>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Organization role too low (code `org_role_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn update_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Path(organization_id): Path<Uuid>,
+    Json(payload): Json<UpdateOrganizationRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, OrgRole::Admin).await {
+        return rejection;
+    }
+
+    let organization_service = OrganizationService::new(&state.pool);
+
+    match organization_service.update_organization(auth_user.user.id, organization_id, payload, &client).await {
+        Ok(organization) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(organization, "Organization updated")),
+        ).into_response(),
+        Err(e) => organization_error("Update organization", e),
+    }
+}
+
+/// Delete an organization and its memberships (organization owners)
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{id}",
+    params(("id" = Uuid, Path, description = "Organization ID")),
+    responses(
+        (status = 200, description = "Organization deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Organization role too low (code `org_role_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn delete_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Path(organization_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, OrgRole::Owner).await {
+        return rejection;
+    }
+
+    let organization_service = OrganizationService::new(&state.pool);
+
+    match organization_service.delete_organization(auth_user.user.id, organization_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Organization deleted", "Organization deleted successfully")),
+        ).into_response(),
+        Err(e) => organization_error("Delete organization", e),
+    }
+}
+
+/// List an organization's members
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{id}/members",
+    params(("id" = Uuid, Path, description = "Organization ID")),
+    responses(
+        (status = 200, description = "Members retrieved", body = ApiResponse<Vec<OrganizationMember>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn list_members(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(organization_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, OrgRole::Member).await {
+        return rejection;
+    }
+
+    match OrganizationService::new(&state.pool).list_members(organization_id).await {
+        Ok(members) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(members, "Members retrieved")),
+        ).into_response(),
+        Err(e) => organization_error("List members", e),
+    }
+}
+
+/// Add an existing user to an organization (organization admins; only owners can add owners)
+#[utoipa::path(
+    post,
+    path = "/api/organizations/{id}/members",
+    params(("id" = Uuid, Path, description = "Organization ID")),
+    request_body = AddMemberRequest,
+    responses(
+        (status = 201, description = "Member added", body = ApiResponse<OrganizationMember>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Organization role too low (code `org_role_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization or user not found", body = ErrorResponse),
+        (status = 409, description = "User is already a member", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn add_member(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Path(organization_id): Path<Uuid>,
+    Json(payload): Json<AddMemberRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let required = payload.role.max(OrgRole::Admin);
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, required).await {
+        return rejection;
+    }
+
+    let organization_service = OrganizationService::new(&state.pool);
+
+    match organization_service.add_member(auth_user.user.id, organization_id, payload, &client).await {
+        Ok(member) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(member, "Member added")),
+        ).into_response(),
+        Err(e) => organization_error("Add member", e),
+    }
+}
+
+/// Change a member's role (organization admins; only owners can grant or revoke owner)
+#[utoipa::path(
+    put,
+    path = "/api/organizations/{id}/members/{user_id}",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = UpdateMemberRequest,
+    responses(
+        (status = 200, description = "Member updated", body = ApiResponse<OrganizationMember>),
+        (status = 400, description = "The organization would be left without an owner", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Organization role too low (code `org_role_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization or member not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn update_member(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Path((organization_id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> impl IntoResponse {
+    let organization_service = OrganizationService::new(&state.pool);
+
+    let target_role = match organization_service.membership(organization_id, user_id).await {
+        Ok(membership) => membership.map(|membership| membership.role).unwrap_or(OrgRole::Member),
+        Err(e) => return organization_error("Update member", e),
+    };
+    let required = payload.role.max(target_role).max(OrgRole::Admin);
+    if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, required).await {
+        return rejection;
+    }
+
+    match organization_service
+        .update_member(auth_user.user.id, organization_id, user_id, payload.role, &client)
+        .await
+    {
+        Ok(member) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(member, "Member updated")),
+        ).into_response(),
+        Err(e) => organization_error("Update member", e),
+    }
+}
+
+/// Remove a member (organization admins; only owners can remove owners). Members can remove themselves.
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{id}/members/{user_id}",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Member removed", body = ApiResponse<String>),
+        (status = 400, description = "The organization would be left without an owner", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Organization role too low (code `org_role_required`)", body = ErrorResponse),
+        (status = 404, description = "Organization or member not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "organization"
+)]
+pub async fn remove_member(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Path((organization_id, user_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let organization_service = OrganizationService::new(&state.pool);
+
+    if user_id != auth_user.user.id {
+        let target_role = match organization_service.membership(organization_id, user_id).await {
+            Ok(membership) => membership.map(|membership| membership.role).unwrap_or(OrgRole::Member),
+            Err(e) => return organization_error("Remove member", e),
+        };
+        let required = target_role.max(OrgRole::Admin);
+        if let Err(rejection) = require_org_role(&state, &auth_user, organization_id, required).await {
+            return rejection;
+        }
+    }
+
+    match organization_service.remove_member(auth_user.user.id, organization_id, user_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Member removed", "Member removed successfully")),
+        ).into_response(),
+        Err(e) => organization_error("Remove member", e),
+    }
+}
+
+/// The caller's membership in the organization, if their role is at least
+/// `required`. Non-members get 404 so organizations cannot be probed.
+async fn require_org_role(
+    state: &AppState,
+    auth_user: &AuthUser,
+    organization_id: Uuid,
+    required: OrgRole,
+) -> Result<Membership, Response> {
+    let membership = OrganizationService::new(&state.pool)
+        .membership(organization_id, auth_user.user.id)
+        .await
+        .map_err(|e| organization_error("Membership lookup", e))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new("Organization not found")),
+            ).into_response()
+        })?;
+
+    if membership.role < required {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::with_code(
+                "org_role_required",
+                "Your role in this organization does not allow this",
+                Some(serde_json::json!({ "required": required })),
+            )),
+        ).into_response());
+    }
+
+    Ok(membership)
+}
+
+fn organization_error(context: &str, e: anyhow::Error) -> Response {
+    tracing::error!("{} error: {:?}", context, e);
+    let message = e.to_string();
+    let status = if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already") {
+        StatusCode::CONFLICT
+    } else if message.contains("cannot") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, Json(ErrorResponse::new(message))).into_response()
+}
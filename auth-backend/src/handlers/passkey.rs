@@ -0,0 +1,314 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{
+        passkey::{
+            FinishPasskeyLoginRequest, FinishPasskeyRegistrationRequest, RenamePasskeyRequest,
+            StartPasskeyLoginRequest,
+        },
+        response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
+    },
+    services::{auth_service::AuthService, passkey_service::PasskeyService},
+    middleware::auth::{AuthUser, RequireSession},
+    utils::cookies::SessionCookies,
+    AppState,
+};
+
+/// Ceremony failures are the client's fault; database errors are ours.
+fn ceremony_error_status(e: &anyhow::Error, client_status: StatusCode) -> StatusCode {
+    if e.downcast_ref::<sqlx::Error>().is_some() {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        client_status
+    }
+}
+
+/// Start passkey registration
+#[utoipa::path(
+    post,
+    path = "/api/users/passkeys/register/start",
+    responses(
+        (status = 200, description = "Credential creation options", body = ApiResponse<PublicKeyCredentialCreationOptions>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn start_registration(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+) -> impl IntoResponse {
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.start_registration(&auth_user.user).await {
+        Ok(options) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(options, "Passkey registration started")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Passkey registration start error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Finish passkey registration
+#[utoipa::path(
+    post,
+    path = "/api/users/passkeys/register/finish",
+    request_body = FinishPasskeyRegistrationRequest,
+    responses(
+        (status = 201, description = "Passkey registered", body = ApiResponse<Passkey>),
+        (status = 400, description = "Invalid credential or challenge", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn finish_registration(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Json(payload): Json<FinishPasskeyRegistrationRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.finish_registration(&auth_user.user, payload).await {
+        Ok(passkey) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(passkey, "Passkey registered successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Passkey registration error: {:?}", e);
+            let status = ceremony_error_status(&e, StatusCode::BAD_REQUEST);
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// Start passkey login
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/login/start",
+    request_body = StartPasskeyLoginRequest,
+    responses(
+        (status = 200, description = "Credential request options", body = ApiResponse<PublicKeyCredentialRequestOptions>),
+        (status = 400, description = "Validation error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn start_login(
+    State(state): State<AppState>,
+    Json(payload): Json<StartPasskeyLoginRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.start_authentication(payload.email.as_deref()).await {
+        Ok(options) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(options, "Passkey login started")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Passkey login start error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Finish passkey login
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/login/finish",
+    request_body = FinishPasskeyLoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = ApiResponse<AuthResponse>),
+        (status = 401, description = "Passkey verification failed", body = ErrorResponse),
+        (status = 403, description = "Email not verified", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+pub async fn finish_login(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(payload): Json<FinishPasskeyLoginRequest>,
+) -> impl IntoResponse {
+    let auth_service = AuthService::new(&state.pool, &state.jwt_keys, &state.settings, &state.revoked_tokens, &state.hashing_pool);
+
+    match auth_service.login_with_passkey(payload, &client).await {
+        Ok(auth_response) => {
+            let (jar, auth_response) = SessionCookies::new(&state.settings).issue(jar, auth_response);
+            (
+                StatusCode::OK,
+                jar,
+                Json(ApiResponse::success(auth_response, "Login successful")),
+            ).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Passkey login error: {:?}", e);
+            let status = if e.to_string().contains("Email not verified") {
+                StatusCode::FORBIDDEN
+            } else {
+                ceremony_error_status(&e, StatusCode::UNAUTHORIZED)
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// List passkeys
+#[utoipa::path(
+    get,
+    path = "/api/users/passkeys",
+    responses(
+        (status = 200, description = "Passkeys retrieved", body = ApiResponse<Vec<Passkey>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn list_passkeys(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+) -> impl IntoResponse {
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.list_passkeys(auth_user.user.id).await {
+        Ok(passkeys) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(passkeys, "Passkeys retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("List passkeys error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Rename a passkey
+#[utoipa::path(
+    put,
+    path = "/api/users/passkeys/{id}",
+    params(("id" = Uuid, Path, description = "Passkey ID")),
+    request_body = RenamePasskeyRequest,
+    responses(
+        (status = 200, description = "Passkey renamed", body = ApiResponse<String>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 404, description = "Passkey not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn rename_passkey(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Path(passkey_id): Path<Uuid>,
+    Json(payload): Json<RenamePasskeyRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.rename_passkey(auth_user.user.id, passkey_id, &payload.name).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Passkey renamed", "Passkey renamed successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Rename passkey error: {:?}", e);
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// Delete a passkey
+#[utoipa::path(
+    delete,
+    path = "/api/users/passkeys/{id}",
+    params(("id" = Uuid, Path, description = "Passkey ID")),
+    responses(
+        (status = 200, description = "Passkey deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 404, description = "Passkey not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn delete_passkey(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Path(passkey_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let passkey_service = PasskeyService::new(&state.pool, &state.settings);
+
+    match passkey_service.delete_passkey(auth_user.user.id, passkey_id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Passkey deleted", "Passkey deleted successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Delete passkey error: {:?}", e);
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
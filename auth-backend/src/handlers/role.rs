@@ -0,0 +1,339 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{
+        response::{ApiResponse, ErrorResponse},
+        role::{
+            permissions::{RolesManage, UsersRead},
+            CreatePermissionRequest, CreateRoleRequest, SetUserRolesRequest, UpdateRoleRequest,
+        },
+        session::ClientInfo,
+    },
+    services::role_service::RoleService,
+    middleware::auth::{AuthUser, RequirePermission},
+    AppState,
+};
+
+/// List roles with their permissions
+#[utoipa::path(
+    get,
+    path = "/api/admin/roles",
+    responses(
+        (status = 200, description = "Roles retrieved", body = ApiResponse<Vec<Role>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn list_roles(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+) -> impl IntoResponse {
+    match RoleService::new(&state.pool).list_roles().await {
+        Ok(roles) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(roles, "Roles retrieved")),
+        ).into_response(),
+        Err(e) => role_error("List roles", e),
+    }
+}
+
+/// Create a role
+#[utoipa::path(
+    post,
+    path = "/api/admin/roles",
+    request_body = CreateRoleRequest,
+    responses(
+        (status = 201, description = "Role created", body = ApiResponse<Role>),
+        (status = 400, description = "Validation error or unknown permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Role already exists", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn create_role(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Json(payload): Json<CreateRoleRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    match RoleService::new(&state.pool).create_role(auth_user.user.id, payload, &client).await {
+        Ok(role) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(role, "Role created")),
+        ).into_response(),
+        Err(e) => role_error("Create role", e),
+    }
+}
+
+/// Replace a role's description and permissions
+#[utoipa::path(
+    put,
+    path = "/api/admin/roles/{id}",
+    params(("id" = Uuid, Path, description = "Role ID")),
+    request_body = UpdateRoleRequest,
+    responses(
+        (status = 200, description = "Role updated", body = ApiResponse<Role>),
+        (status = 400, description = "Validation error, unknown permission or the admin role", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn update_role(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Path(role_id): Path<Uuid>,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    match RoleService::new(&state.pool).update_role(auth_user.user.id, role_id, payload, &client).await {
+        Ok(role) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(role, "Role updated")),
+        ).into_response(),
+        Err(e) => role_error("Update role", e),
+    }
+}
+
+/// Delete a role
+#[utoipa::path(
+    delete,
+    path = "/api/admin/roles/{id}",
+    params(("id" = Uuid, Path, description = "Role ID")),
+    responses(
+        (status = 200, description = "Role deleted", body = ApiResponse<String>),
+        (status = 400, description = "Built-in roles cannot be deleted", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn delete_role(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Path(role_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match RoleService::new(&state.pool).delete_role(auth_user.user.id, role_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Role deleted", "Role deleted successfully")),
+        ).into_response(),
+        Err(e) => role_error("Delete role", e),
+    }
+}
+
+/// List permissions
+#[utoipa::path(
+    get,
+    path = "/api/admin/permissions",
+    responses(
+        (status = 200, description = "Permissions retrieved", body = ApiResponse<Vec<PermissionDefinition>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn list_permissions(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+) -> impl IntoResponse {
+    match RoleService::new(&state.pool).list_permissions().await {
+        Ok(permissions) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(permissions, "Permissions retrieved")),
+        ).into_response(),
+        Err(e) => role_error("List permissions", e),
+    }
+}
+
+/// Create a custom permission for other services to check
+#[utoipa::path(
+    post,
+    path = "/api/admin/permissions",
+    request_body = CreatePermissionRequest,
+    responses(
+        (status = 201, description = "Permission created", body = ApiResponse<PermissionDefinition>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Permission already exists", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn create_permission(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Json(payload): Json<CreatePermissionRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    match RoleService::new(&state.pool).create_permission(auth_user.user.id, payload, &client).await {
+        Ok(permission) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(permission, "Permission created")),
+        ).into_response(),
+        Err(e) => role_error("Create permission", e),
+    }
+}
+
+/// Delete a custom permission
+#[utoipa::path(
+    delete,
+    path = "/api/admin/permissions/{id}",
+    params(("id" = Uuid, Path, description = "Permission ID")),
+    responses(
+        (status = 200, description = "Permission deleted", body = ApiResponse<String>),
+        (status = 400, description = "Built-in permissions cannot be deleted", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Permission not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn delete_permission(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Path(permission_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match RoleService::new(&state.pool).delete_permission(auth_user.user.id, permission_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Permission deleted", "Permission deleted successfully")),
+        ).into_response(),
+        Err(e) => role_error("Delete permission", e),
+    }
+}
+
+/// Get a user's roles and permissions
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}/roles",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User roles retrieved", body = ApiResponse<UserRoles>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn get_user_roles(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    _permission: RequirePermission<UsersRead>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match RoleService::new(&state.pool).user_roles(user_id).await {
+        Ok(user_roles) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(user_roles, "User roles retrieved")),
+        ).into_response(),
+        Err(e) => role_error("Get user roles", e),
+    }
+}
+
+/// Replace a user's roles. Takes effect when their access token is next refreshed.
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/roles",
+    params(("id" = Uuid, Path, description = "User ID")),
+    request_body = SetUserRolesRequest,
+    responses(
+        (status = 200, description = "User roles updated", body = ApiResponse<UserRoles>),
+        (status = 400, description = "Validation error, unknown role or removing your own admin role", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn set_user_roles(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<RolesManage>,
+    client: ClientInfo,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SetUserRolesRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    match RoleService::new(&state.pool).set_user_roles(auth_user.user.id, user_id, payload, &client).await {
+        Ok(user_roles) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(user_roles, "User roles updated")),
+        ).into_response(),
+        Err(e) => role_error("Set user roles", e),
+    }
+}
+
+fn role_error(context: &str, e: anyhow::Error) -> Response {
+    tracing::error!("{} error: {:?}", context, e);
+    let message = e.to_string();
+    let status = if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("already exists") {
+        StatusCode::CONFLICT
+    } else if message.starts_with("Unknown") || message.contains("cannot") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, Json(ErrorResponse::new(message))).into_response()
+}
@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{
+        response::{ApiResponse, ErrorResponse},
+        role::permissions::ServiceAccountsManage,
+        service_account::CreateServiceAccountRequest,
+        session::ClientInfo,
+    },
+    services::service_account_service::ServiceAccountService,
+    middleware::auth::{AuthUser, RequirePermission},
+    AppState,
+};
+
+/// List service accounts
+#[utoipa::path(
+    get,
+    path = "/api/admin/service-accounts",
+    responses(
+        (status = 200, description = "Service accounts retrieved", body = ApiResponse<Vec<ServiceAccount>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn list_service_accounts(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    _permission: RequirePermission<ServiceAccountsManage>,
+) -> impl IntoResponse {
+    let service_account_service = ServiceAccountService::new(&state.pool, &state.revoked_tokens);
+
+    match service_account_service.list_service_accounts().await {
+        Ok(service_accounts) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(service_accounts, "Service accounts retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("List service accounts error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Create a service account. The client secret is only shown in this response.
+#[utoipa::path(
+    post,
+    path = "/api/admin/service-accounts",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 201, description = "Service account created", body = ApiResponse<ServiceAccountCredentials>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn create_service_account(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<ServiceAccountsManage>,
+    client: ClientInfo,
+    Json(payload): Json<CreateServiceAccountRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        let error_details = serde_json::to_value(&errors).unwrap_or_default();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_details("Validation failed", error_details)),
+        ).into_response();
+    }
+
+    let service_account_service = ServiceAccountService::new(&state.pool, &state.revoked_tokens);
+
+    match service_account_service.create_service_account(auth_user.user.id, payload, &client).await {
+        Ok(credentials) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success(credentials, "Service account created. Copy the client secret now, it will not be shown again")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Create service account error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Issue a new client secret, replacing the current one
+#[utoipa::path(
+    post,
+    path = "/api/admin/service-accounts/{id}/rotate-secret",
+    params(("id" = Uuid, Path, description = "Service account ID")),
+    responses(
+        (status = 200, description = "Client secret rotated", body = ApiResponse<ServiceAccountCredentials>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Service account not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn rotate_service_account_secret(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<ServiceAccountsManage>,
+    client: ClientInfo,
+    Path(service_account_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let service_account_service = ServiceAccountService::new(&state.pool, &state.revoked_tokens);
+
+    match service_account_service.rotate_secret(auth_user.user.id, service_account_id, &client).await {
+        Ok(credentials) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(credentials, "Client secret rotated. Copy it now, it will not be shown again")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Rotate service account secret error: {:?}", e);
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
+
+/// Delete a service account
+#[utoipa::path(
+    delete,
+    path = "/api/admin/service-accounts/{id}",
+    params(("id" = Uuid, Path, description = "Service account ID")),
+    responses(
+        (status = 200, description = "Service account deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Service account not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "admin"
+)]
+pub async fn delete_service_account(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _permission: RequirePermission<ServiceAccountsManage>,
+    client: ClientInfo,
+    Path(service_account_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let service_account_service = ServiceAccountService::new(&state.pool, &state.revoked_tokens);
+
+    match service_account_service.delete_service_account(auth_user.user.id, service_account_id, &client).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Service account deleted", "Service account deleted successfully")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Delete service account error: {:?}", e);
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+        }
+    }
+}
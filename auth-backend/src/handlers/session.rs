@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        response::{ApiResponse, ErrorResponse},
+        session::RevokedSessions,
+    },
+    services::session_service::SessionService,
+    middleware::auth::{AuthUser, RequireSession},
+    AppState,
+};
+
+/// List active sessions
+#[utoipa::path(
+    get,
+    path = "/api/users/sessions",
+    responses(
+        (status = 200, description = "Sessions retrieved", body = ApiResponse<Vec<Session>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    RequireSession(session_id): RequireSession,
+) -> impl IntoResponse {
+    let session_service = SessionService::new(&state.pool, &state.revoked_tokens);
+
+    match session_service.list_sessions(auth_user.user.id, session_id).await {
+        Ok(sessions) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(sessions, "Sessions retrieved")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("List sessions error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Revoke a session
+#[utoipa::path(
+    delete,
+    path = "/api/users/sessions/{id}",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session revoked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    _session: RequireSession,
+    Path(session_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let session_service = SessionService::new(&state.pool, &state.revoked_tokens);
+
+    match session_service.revoke_session(auth_user.user.id, session_id).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(ApiResponse::success("Session revoked", "Session revoked successfully")),
+        ).into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Session not found")),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Revoke session error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
+
+/// Sign out all other sessions
+#[utoipa::path(
+    post,
+    path = "/api/users/sessions/revoke-others",
+    responses(
+        (status = 200, description = "Other sessions revoked", body = ApiResponse<RevokedSessions>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "user"
+)]
+pub async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    RequireSession(session_id): RequireSession,
+) -> impl IntoResponse {
+    let session_service = SessionService::new(&state.pool, &state.revoked_tokens);
+
+    match session_service.revoke_other_sessions(auth_user.user.id, session_id).await {
+        Ok(revoked) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(
+                RevokedSessions { revoked },
+                "Other sessions signed out",
+            )),
+        ).into_response(),
+        Err(e) => {
+            tracing::error!("Revoke other sessions error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e.to_string())),
+            ).into_response()
+        }
+    }
+}
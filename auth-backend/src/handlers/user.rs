@@ -7,12 +7,15 @@ use axum::{
 use validator::Validate;
 
 use crate::{
+    handlers::hashing_pool_saturated,
     models::{
         user::{UpdateProfileRequest, ChangePasswordRequest, UserProfile},
         response::{ApiResponse, ErrorResponse},
+        session::ClientInfo,
     },
-    services::user_service::UserService,
-    middleware::auth::AuthUser,
+    services::{session_service::SessionService, user_service::UserService, verification_service::VerificationService},
+    middleware::auth::{AuthUser, PasswordChangeUser, RequireSession, RequireVerifiedEmail},
+    utils::validation::{with_password_violations, PasswordPolicy, PasswordPolicyViolation},
     AppState,
 };
 
@@ -36,6 +39,8 @@ pub async fn get_profile(auth_user: AuthUser) -> impl IntoResponse {
 }
 
 /// Update user profile
+///
+/// Changing the email address marks it unverified and sends a verification link to the new address.
 #[utoipa::path(
     put,
     path = "/api/users/profile",
@@ -43,7 +48,8 @@ pub async fn get_profile(auth_user: AuthUser) -> impl IntoResponse {
     responses(
         (status = 200, description = "Profile updated successfully", body = ApiResponse<UserProfile>),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Email verification required", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "user"
@@ -51,6 +57,8 @@ pub async fn get_profile(auth_user: AuthUser) -> impl IntoResponse {
 pub async fn update_profile(
     State(state): State<AppState>,
     auth_user: AuthUser,
+    _require_verified: RequireVerifiedEmail,
+    client: ClientInfo,
     Json(payload): Json<UpdateProfileRequest>,
 ) -> impl IntoResponse {
     // Validate request
@@ -62,10 +70,17 @@ pub async fn update_profile(
         ).into_response();
     }
 
-    let user_service = UserService::new(&state.pool);
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
     
-    match user_service.update_profile(auth_user.user.id, payload).await {
+    match user_service.update_profile(auth_user.user.id, payload, &client).await {
         Ok(user) => {
+            if user.email != auth_user.user.email {
+                let verification_service = VerificationService::new(&state.pool, &state.mailer, &state.settings);
+                if let Err(e) = verification_service.send_verification(&user).await {
+                    tracing::error!("Verification email error: {:?}", e);
+                }
+            }
+
             let profile: UserProfile = user.into();
             (
                 StatusCode::OK,
@@ -90,18 +105,27 @@ pub async fn update_profile(
     responses(
         (status = 200, description = "Password changed successfully", body = ApiResponse<String>),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse),
+        (status = 503, description = "Password hashing queue is full (code `server_busy`)", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "user"
 )]
 pub async fn change_password(
     State(state): State<AppState>,
-    auth_user: AuthUser,
+    // The one route still open to users who must change their password
+    PasswordChangeUser(auth_user): PasswordChangeUser,
+    RequireSession(session_id): RequireSession,
+    client: ClientInfo,
     Json(payload): Json<ChangePasswordRequest>,
 ) -> impl IntoResponse {
     // Validate request
-    if let Err(errors) = payload.validate() {
+    let user = &auth_user.user;
+    let personal_info = [user.email.as_str(), user.name.as_deref().unwrap_or_default()];
+    let mut violations = PasswordPolicy::from_settings(&state.settings).check(&payload.new_password, &personal_info);
+    violations.extend(state.breached_passwords.check(&payload.new_password));
+    if let Err(errors) = with_password_violations(payload.validate(), "new_password", violations) {
         let error_details = serde_json::to_value(&errors).unwrap_or_default();
         return (
             StatusCode::BAD_REQUEST,
@@ -109,14 +133,36 @@ pub async fn change_password(
         ).into_response();
     }
 
-    let user_service = UserService::new(&state.pool);
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
     
-    match user_service.change_password(auth_user.user.id, payload).await {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(ApiResponse::success("Password changed", "Password changed successfully")),
-        ).into_response(),
+    match user_service.change_password(auth_user.user.id, payload, &client).await {
+        Ok(_) => {
+            // Sign out everywhere else; the session that changed the password stays valid
+            let session_service = SessionService::new(&state.pool, &state.revoked_tokens);
+            if let Err(e) = session_service
+                .revoke_other_sessions(auth_user.user.id, session_id)
+                .await
+            {
+                tracing::error!("Session revocation after password change error: {:?}", e);
+            }
+
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success("Password changed", "Password changed successfully")),
+            ).into_response()
+        }
         Err(e) => {
+            if let Some(response) = hashing_pool_saturated(&e) {
+                return response;
+            }
+            if let Some(PasswordPolicyViolation(errors)) = e.downcast_ref::<PasswordPolicyViolation>() {
+                let error_details = serde_json::to_value(errors).unwrap_or_default();
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::with_details("Validation failed", error_details)),
+                ).into_response();
+            }
+
             tracing::error!("Password change error: {:?}", e);
             let status = if e.to_string().contains("Invalid current password") {
                 StatusCode::BAD_REQUEST
@@ -134,7 +180,8 @@ pub async fn change_password(
     path = "/api/users/account",
     responses(
         (status = 200, description = "Account deleted successfully", body = ApiResponse<String>),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Personal access tokens are not accepted (code `session_required`)", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "user"
@@ -142,10 +189,12 @@ pub async fn change_password(
 pub async fn delete_account(
     State(state): State<AppState>,
     auth_user: AuthUser,
+    _session: RequireSession,
+    client: ClientInfo,
 ) -> impl IntoResponse {
-    let user_service = UserService::new(&state.pool);
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
     
-    match user_service.delete_account(auth_user.user.id).await {
+    match user_service.delete_account(auth_user.user.id, &client).await {
         Ok(_) => (
             StatusCode::OK,
             Json(ApiResponse::success("Account deleted", "Account deleted successfully")),
@@ -167,7 +216,8 @@ pub async fn delete_account(
     responses(
         (status = 200, description = "Avatar uploaded successfully", body = ApiResponse<String>),
         (status = 400, description = "Invalid file", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Email verification required", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "user"
@@ -175,11 +225,13 @@ pub async fn delete_account(
 pub async fn upload_avatar(
     State(state): State<AppState>,
     auth_user: AuthUser,
+    _require_verified: RequireVerifiedEmail,
+    client: ClientInfo,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
-    let user_service = UserService::new(&state.pool);
+    let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
     
-    match user_service.upload_avatar(auth_user.user.id, &mut multipart).await {
+    match user_service.upload_avatar(auth_user.user.id, &mut multipart, &client).await {
         Ok(avatar_url) => (
             StatusCode::OK,
             Json(ApiResponse::success(avatar_url, "Avatar uploaded successfully")),
@@ -3,16 +3,28 @@ pub mod database;
 pub mod handlers;
 pub mod middleware;
 pub mod models;
+pub mod routes;
 pub mod services;
 pub mod utils;
 
 use sqlx::SqlitePool;
 use std::sync::Arc;
-use crate::{config::Settings, utils::jwt::JwtKeys};
+use crate::{
+    config::Settings,
+    services::{
+        breached_passwords::BreachedPasswords, hashing_pool::HashingPool, mailer::Mailer,
+        token_revocation::TokenRevocationStore,
+    },
+    utils::jwt::JwtKeys,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: SqlitePool,
     pub jwt_keys: Arc<JwtKeys>,
     pub settings: Arc<Settings>,
+    pub mailer: Arc<dyn Mailer>,
+    pub revoked_tokens: Arc<TokenRevocationStore>,
+    pub breached_passwords: Arc<BreachedPasswords>,
+    pub hashing_pool: Arc<HashingPool>,
 }
\ No newline at end of file
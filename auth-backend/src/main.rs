@@ -1,16 +1,18 @@
 use auth_backend::{
     config::Settings,
     database::connection::create_connection_pool,
-    handlers::{auth, user, admin, analytics},
-    middleware::{cors::cors_layer, logging::logging_layer},
+    handlers::{
+        access_token, auth, user, admin, analytics, metrics, mfa, oauth, organization, passkey, role, service_account,
+        session,
+    },
+    routes,
+    services::{
+        breached_passwords::BreachedPasswords, hashing_pool::HashingPool, mailer::build_mailer,
+        token_revocation::TokenRevocationStore,
+    },
     utils::jwt::JwtKeys,
 };
-use axum::{
-    routing::{get, post, put, delete},
-    Router,
-};
-use std::sync::Arc;
-use tower_http::services::ServeDir;
+use std::{net::SocketAddr, sync::Arc};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::OpenApi;
 
@@ -26,7 +28,33 @@ use utoipa::OpenApi;
         auth::login,
         auth::logout,
         auth::refresh_token,
+        auth::verify_email,
+        auth::resend_verification,
+        auth::forgot_password,
+        auth::reset_password,
         auth::get_current_user,
+        auth::switch_organization,
+        auth::jwks,
+        metrics::metrics,
+        mfa::verify_mfa,
+        mfa::enroll_totp,
+        mfa::confirm_totp,
+        mfa::disable_totp,
+        mfa::get_recovery_code_status,
+        mfa::regenerate_recovery_codes,
+        passkey::start_registration,
+        passkey::finish_registration,
+        passkey::start_login,
+        passkey::finish_login,
+        passkey::list_passkeys,
+        passkey::rename_passkey,
+        passkey::delete_passkey,
+        session::list_sessions,
+        session::revoke_session,
+        session::revoke_other_sessions,
+        access_token::list_tokens,
+        access_token::create_token,
+        access_token::revoke_token,
         user::get_profile,
         user::update_profile,
         user::change_password,
@@ -34,13 +62,92 @@ use utoipa::OpenApi;
         user::upload_avatar,
         admin::get_dashboard_stats,
         admin::get_recent_activity,
+        admin::revoke_user_sessions,
+        admin::unlock_user,
+        admin::require_password_change,
+        admin::list_audit_events,
+        role::list_roles,
+        role::create_role,
+        role::update_role,
+        role::delete_role,
+        role::list_permissions,
+        role::create_permission,
+        role::delete_permission,
+        role::get_user_roles,
+        role::set_user_roles,
+        organization::list_organizations,
+        organization::create_organization,
+        organization::get_organization,
+        organization::update_organization,
+        organization::delete_organization,
+        organization::list_members,
+        organization::add_member,
+        organization::update_member,
+        organization::remove_member,
+        service_account::list_service_accounts,
+        service_account::create_service_account,
+        service_account::rotate_service_account_secret,
+        service_account::delete_service_account,
+        oauth::token,
         analytics::logins_per_day,
     ),
     components(schemas(
         auth_backend::models::auth::RegisterRequest,
         auth_backend::models::auth::LoginRequest,
         auth_backend::models::auth::AuthResponse,
+        auth_backend::models::auth::LoginResponse,
+        auth_backend::models::auth::MfaChallenge,
         auth_backend::models::auth::RefreshRequest,
+        auth_backend::models::auth::VerifyEmailRequest,
+        auth_backend::models::auth::ResendVerificationRequest,
+        auth_backend::models::auth::ForgotPasswordRequest,
+        auth_backend::models::auth::ResetPasswordRequest,
+        auth_backend::models::auth::JsonWebKey,
+        auth_backend::models::auth::JsonWebKeySet,
+        auth_backend::models::mfa::MfaVerifyRequest,
+        auth_backend::models::mfa::TotpCodeRequest,
+        auth_backend::models::mfa::TotpEnrollment,
+        auth_backend::models::mfa::MfaCodeRequest,
+        auth_backend::models::mfa::RecoveryCodes,
+        auth_backend::models::mfa::RecoveryCodeStatus,
+        auth_backend::models::passkey::PublicKeyCredentialCreationOptions,
+        auth_backend::models::passkey::PublicKeyCredentialRequestOptions,
+        auth_backend::models::passkey::FinishPasskeyRegistrationRequest,
+        auth_backend::models::passkey::StartPasskeyLoginRequest,
+        auth_backend::models::passkey::FinishPasskeyLoginRequest,
+        auth_backend::models::passkey::RenamePasskeyRequest,
+        auth_backend::models::passkey::Passkey,
+        auth_backend::models::session::Session,
+        auth_backend::models::session::RevokedSessions,
+        auth_backend::models::access_token::TokenScope,
+        auth_backend::models::access_token::CreateAccessTokenRequest,
+        auth_backend::models::access_token::AccessToken,
+        auth_backend::models::access_token::CreatedAccessToken,
+        auth_backend::models::role::Role,
+        auth_backend::models::role::PermissionDefinition,
+        auth_backend::models::role::CreateRoleRequest,
+        auth_backend::models::role::UpdateRoleRequest,
+        auth_backend::models::role::CreatePermissionRequest,
+        auth_backend::models::role::SetUserRolesRequest,
+        auth_backend::models::role::UserRoles,
+        auth_backend::models::organization::OrgRole,
+        auth_backend::models::organization::Organization,
+        auth_backend::models::organization::UserOrganization,
+        auth_backend::models::organization::OrganizationMember,
+        auth_backend::models::organization::CreateOrganizationRequest,
+        auth_backend::models::organization::UpdateOrganizationRequest,
+        auth_backend::models::organization::AddMemberRequest,
+        auth_backend::models::organization::UpdateMemberRequest,
+        auth_backend::models::organization::SwitchOrganizationRequest,
+        auth_backend::models::service_account::CreateServiceAccountRequest,
+        auth_backend::models::service_account::ServiceAccount,
+        auth_backend::models::service_account::ServiceAccountCredentials,
+        auth_backend::models::service_account::TokenRequest,
+        auth_backend::models::service_account::TokenResponse,
+        auth_backend::models::service_account::OAuthError,
+        auth_backend::models::user::PasswordChangeReason,
+        auth_backend::models::audit::AuditEvent,
+        auth_backend::models::audit::AuditEventPage,
         auth_backend::models::user::User,
         auth_backend::models::user::UserProfile,
         auth_backend::models::user::UpdateProfileRequest,
@@ -51,7 +158,10 @@ use utoipa::OpenApi;
     tags(
         (name = "auth", description = "Authentication endpoints"),
         (name = "user", description = "User management endpoints"),
-        (name = "admin", description = "Admin endpoints")
+        (name = "organization", description = "Organizations and memberships"),
+        (name = "admin", description = "Admin endpoints"),
+        (name = "oauth", description = "OAuth2 client credentials grant for service accounts"),
+        (name = "metrics", description = "Operational metrics")
     )
 )]
 #[allow(dead_code)]
@@ -77,33 +187,35 @@ async fn main() -> anyhow::Result<()> {
     // Run migrations
     sqlx::migrate!("./migrations").run(&pool).await?;
     
+    // Start the password hashing workers; fails fast on invalid parameters
+    let hashing_pool = Arc::new(HashingPool::start(&settings)?);
+
     // Initialize JWT keys
-    let jwt_keys = Arc::new(JwtKeys::new(
-        settings.jwt_secret.clone(),
-        settings.jwt_refresh_secret.clone(),
-    ));
+    let jwt_keys = Arc::new(JwtKeys::from_settings(&settings)?);
+
+    // Initialize outbound mailer
+    let mailer = build_mailer(&settings)?;
+
+    // Load the access token denylist and keep it in sync with the database
+    let revoked_tokens = Arc::new(TokenRevocationStore::load(pool.clone()).await?);
+    revoked_tokens.clone().spawn_sync_task();
+
+    // Map the offline breached password corpus, if configured
+    let breached_passwords = Arc::new(BreachedPasswords::load(&settings)?);
 
     // Create application state
     let app_state = auth_backend::AppState {
         pool,
         jwt_keys,
         settings: Arc::new(settings.clone()),
+        mailer,
+        revoked_tokens,
+        breached_passwords,
+        hashing_pool,
     };
 
     // Build our application with routes
-    let app = Router::new()
-        // API routes
-        .nest("/api", api_routes())
-        
-        // Static file serving for avatars
-        .nest_service("/uploads", ServeDir::new("uploads"))
-        
-        // Add middleware
-        .layer(cors_layer(&settings.cors_origin))
-        .layer(logging_layer())
-        
-        // Add application state
-        .with_state(app_state);
+    let app = routes::app(app_state);
 
     // TODO: Add Swagger UI back once we figure out the correct integration method
 
@@ -116,32 +228,8 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("API available at http://localhost:{}/api", settings.server_port);
     tracing::info!("Swagger UI temporarily disabled - will be re-enabled later");
     
-    axum::serve(listener, app).await?;
+    // Connection info gives sessions the client address when no trusted proxy header is present
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
-
-fn api_routes() -> Router<auth_backend::AppState> {
-    Router::new()
-        // Authentication routes
-        .route("/auth/register", post(auth::register))
-        .route("/auth/login", post(auth::login))
-        .route("/auth/logout", post(auth::logout))
-        .route("/auth/refresh", post(auth::refresh_token))
-        .route("/auth/me", get(auth::get_current_user))
-        
-        // User routes
-        .route("/users/profile", get(user::get_profile))
-        .route("/users/profile", put(user::update_profile))
-        .route("/users/password", put(user::change_password))
-        .route("/users/account", delete(user::delete_account))
-        .route("/users/avatar", post(user::upload_avatar))
-        
-        // Admin routes
-        .route("/admin/dashboard/stats", get(admin::get_dashboard_stats))
-        .route("/admin/dashboard/activity", get(admin::get_recent_activity))
-        .route("/admin/users", get(admin::list_users))
-        
-        // Analytics routes
-        .route("/analytics/logins-per-day", get(analytics::logins_per_day))
-}
\ No newline at end of file
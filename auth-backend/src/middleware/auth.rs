@@ -1,22 +1,47 @@
 use axum::{
     async_trait,
     extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+    http::{header, request::Parts, Method, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
+use axum_extra::extract::CookieJar;
+use std::marker::PhantomData;
+use uuid::Uuid;
 
 use crate::{
-    models::{auth::Claims, response::ErrorResponse, user::User},
-    services::user_service::UserService,
+    config::EmailVerificationPolicy,
+    models::{
+        access_token::{TokenScope, ACCESS_TOKEN_PREFIX},
+        organization::Membership,
+        response::ErrorResponse,
+        role::{Permission, UserRoles},
+        user::User,
+    },
+    services::{
+        access_token_service::AccessTokenService, organization_service::OrganizationService,
+        role_service::RoleService, user_service::UserService,
+    },
+    utils::cookies::{csrf_token_matches, ACCESS_TOKEN_COOKIE},
     AppState,
 };
 
+#[derive(Clone)]
 pub struct AuthUser {
     pub user: User,
+    /// The session the access token was issued for; `None` for personal access tokens
+    pub session_id: Option<Uuid>,
+    /// Scopes of the personal access token used, or `None` for a session token
+    pub token_scopes: Option<Vec<TokenScope>>,
+    /// From the token's claims, or looked up for personal access tokens
+    pub user_roles: UserRoles,
+    /// Membership in the token's active organization, checked against the
+    /// database; personal access tokens act in the user's default organization
+    pub membership: Option<Membership>,
 }
 
+/// Rejects users who must change their password (code
+/// `password_change_required`); see `PasswordChangeUser` for the one exception.
 #[async_trait]
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = Response;
@@ -25,36 +50,99 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let auth_header = parts
-            .headers
-            .get("Authorization")
-            .and_then(|header| header.to_str().ok())
-            .ok_or_else(|| {
+        let auth_user = AuthUser::authenticate(parts, state).await?;
+
+        if let Some(reason) = auth_user.user.password_change_reason(state.settings.password_max_age_days) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::with_code(
+                    "password_change_required",
+                    "You must change your password before continuing",
+                    Some(serde_json::json!({ "reason": reason })),
+                )),
+            ).into_response());
+        }
+
+        Ok(auth_user)
+    }
+}
+
+/// Authenticates like `AuthUser` but still admits users who must change their
+/// password, so the password change route stays reachable for them.
+pub struct PasswordChangeUser(pub AuthUser);
+
+#[async_trait]
+impl FromRequestParts<AppState> for PasswordChangeUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(PasswordChangeUser(AuthUser::authenticate(parts, state).await?))
+    }
+}
+
+impl AuthUser {
+    /// Token, denylist and user checks shared by the extractors in this module.
+    /// The result is kept in the request extensions, so a handler taking several
+    /// of them authenticates (and touches a personal access token) only once.
+    async fn authenticate(parts: &mut Parts, state: &AppState) -> Result<Self, Response> {
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(auth_user.clone());
+        }
+
+        let auth_user = Self::authenticate_token(parts, state).await?;
+        parts.extensions.insert(auth_user.clone());
+        Ok(auth_user)
+    }
+
+    async fn authenticate_token(parts: &Parts, state: &AppState) -> Result<Self, Response> {
+        let token = Self::access_token(parts, state)?;
+        if token.starts_with(ACCESS_TOKEN_PREFIX) {
+            return Self::authenticate_access_token(parts, state, &token).await;
+        }
+
+        let claims = state.jwt_keys.verify_access_token(&token)
+            .map_err(|_| {
                 (
                     StatusCode::UNAUTHORIZED,
-                    Json(ErrorResponse::new("Missing authorization header")),
+                    Json(ErrorResponse::new("Invalid token")),
                 ).into_response()
             })?;
 
-        let token = auth_header.strip_prefix("Bearer ").ok_or_else(|| {
+        // Client credentials tokens identify a service account, not a user
+        if claims.client_id.is_some() {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::new("Service account tokens are not accepted here")),
+            ).into_response());
+        }
+
+        let session_id = claims.sid.parse().map_err(|_| {
             (
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse::new("Invalid authorization header format")),
+                Json(ErrorResponse::new("Invalid token")),
             ).into_response()
         })?;
 
-        let key = DecodingKey::from_secret(state.jwt_keys.access_secret.as_ref());
-        let token_data = decode::<Claims>(token, &key, &Validation::default())
-            .map_err(|_| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    Json(ErrorResponse::new("Invalid token")),
-                ).into_response()
-            })?;
+        let jti: Uuid = claims.jti.parse().map_err(|_| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::new("Invalid token")),
+            ).into_response()
+        })?;
+
+        if state.revoked_tokens.is_revoked(&jti) {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::new("Token has been revoked")),
+            ).into_response());
+        }
 
-        let user_service = UserService::new(&state.pool);
+        let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
         let user = user_service
-            .get_user_by_id(&token_data.claims.sub.parse().map_err(|_| {
+            .get_user_by_id(&claims.sub.parse().map_err(|_| {
                 (
                     StatusCode::UNAUTHORIZED,
                     Json(ErrorResponse::new("Invalid user ID in token")),
@@ -68,14 +156,222 @@ impl FromRequestParts<AppState> for AuthUser {
                 ).into_response()
             })?;
 
-        Ok(AuthUser { user })
+        let user_roles = UserRoles { roles: claims.roles, permissions: claims.permissions };
+
+        let organization_service = OrganizationService::new(&state.pool);
+        let membership = match claims.org_id.as_deref().map(str::parse::<Uuid>) {
+            Some(Ok(organization_id)) => organization_service.membership(organization_id, user.id).await,
+            Some(Err(_)) => {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(ErrorResponse::new("Invalid token")),
+                ).into_response())
+            }
+            None => Ok(None),
+        }
+        .map_err(|e| {
+            tracing::error!("Membership lookup error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("Internal server error")),
+            ).into_response()
+        })?;
+
+        Ok(AuthUser { user, session_id: Some(session_id), token_scopes: None, user_roles, membership })
+    }
+
+    /// Personal access tokens: `read` admits safe methods, `write` any method.
+    async fn authenticate_access_token(parts: &Parts, state: &AppState, token: &str) -> Result<Self, Response> {
+        let grant = AccessTokenService::new(&state.pool)
+            .authenticate(token)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ErrorResponse::new(e.to_string())),
+                ).into_response()
+            })?;
+
+        let user_service = UserService::new(&state.pool, &state.settings, &state.hashing_pool);
+        let user = user_service.get_user_by_id(&grant.user_id).await.map_err(|_| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::new("User not found")),
+            ).into_response()
+        })?;
+
+        let user_roles = RoleService::new(&state.pool).user_roles(user.id).await.map_err(|e| {
+            tracing::error!("Role lookup error: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("Internal server error")),
+            ).into_response()
+        })?;
+
+        let membership = OrganizationService::new(&state.pool)
+            .default_membership(user.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Membership lookup error: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse::new("Internal server error")),
+                ).into_response()
+            })?;
+
+        let auth_user = AuthUser {
+            user,
+            session_id: None,
+            token_scopes: Some(grant.scopes),
+            user_roles,
+            membership,
+        };
+        let required = match parts.method {
+            Method::GET | Method::HEAD | Method::OPTIONS => TokenScope::Read,
+            _ => TokenScope::Write,
+        };
+        // `write` implies `read`
+        if !auth_user.has_scope(required) && !auth_user.has_scope(TokenScope::Write) {
+            return Err(insufficient_scope(required));
+        }
+
+        Ok(auth_user)
+    }
+
+    pub fn has_permission<P: Permission>(&self) -> bool {
+        self.user_roles.has_permission(P::NAME)
     }
+
+    /// The `RequirePermission<P>` check, for handlers that fall back to
+    /// something else when the permission is missing.
+    // Errors are the extractor's rejection, which is a full response
+    #[allow(clippy::result_large_err)]
+    pub fn require_permission<P: Permission>(&self) -> Result<(), Response> {
+        if !self.has_permission::<P>() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::with_code(
+                    "permission_denied",
+                    "You do not have permission to do this",
+                    Some(serde_json::json!({ "required": P::NAME })),
+                )),
+            ).into_response());
+        }
+
+        if !self.has_scope(TokenScope::Admin) {
+            return Err(insufficient_scope(TokenScope::Admin));
+        }
+
+        Ok(())
+    }
+
+    /// Session tokens carry every scope.
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.token_scopes.as_ref().is_none_or(|scopes| scopes.contains(&scope))
+    }
+
+    /// The bearer token, or in cookie mode the access token cookie. Cookies are
+    /// sent by the browser automatically, so they only count with a CSRF token.
+    // Errors are the extractor's rejection, which is a full response
+    #[allow(clippy::result_large_err)]
+    fn access_token(parts: &Parts, state: &AppState) -> Result<String, Response> {
+        if let Some(auth_header) = parts.headers.get(header::AUTHORIZATION) {
+            return auth_header
+                .to_str()
+                .ok()
+                .and_then(|header| header.strip_prefix("Bearer "))
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    (
+                        StatusCode::UNAUTHORIZED,
+                        Json(ErrorResponse::new("Invalid authorization header format")),
+                    ).into_response()
+                });
+        }
+
+        let jar = CookieJar::from_headers(&parts.headers);
+        if let Some(cookie) = jar.get(ACCESS_TOKEN_COOKIE).filter(|_| state.settings.auth_cookies) {
+            if !csrf_token_matches(&parts.method, &parts.headers, &jar) {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(ErrorResponse::with_code("csrf_token_invalid", "Missing or invalid CSRF token", None)),
+                ).into_response());
+            }
+            return Ok(cookie.value().to_string());
+        }
+
+        Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse::new("Missing authorization header")),
+        ).into_response())
+    }
+}
+
+/// Rejects users whose roles do not grant `P` (code `permission_denied`).
+/// Permission-gated routes are administrative, so personal access tokens also
+/// need the `admin` scope.
+pub struct RequirePermission<P: Permission>(PhantomData<P>);
+
+#[async_trait]
+impl<P: Permission> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+        auth_user.require_permission::<P>()?;
+
+        Ok(RequirePermission(PhantomData))
+    }
+}
+
+/// Rejects personal access tokens and yields the session id, for routes that
+/// manage the session itself or the account's credentials.
+pub struct RequireSession(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequireSession {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // `authenticate` rather than the extractor, so the password change route can use it
+        let auth_user = AuthUser::authenticate(parts, state).await?;
+
+        auth_user.session_id.map(RequireSession).ok_or_else(|| {
+            (
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::with_code(
+                    "session_required",
+                    "This endpoint cannot be used with a personal access token",
+                    None,
+                )),
+            ).into_response()
+        })
+    }
+}
+
+fn insufficient_scope(required: TokenScope) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ErrorResponse::with_code(
+            "insufficient_scope",
+            "The access token does not have the required scope",
+            Some(serde_json::json!({ "required": required })),
+        )),
+    ).into_response()
 }
 
-pub struct RequireRole;
+/// Rejects users whose email address is unverified, unless the
+/// `EMAIL_VERIFICATION` policy is `optional`.
+pub struct RequireVerifiedEmail;
 
 #[async_trait]
-impl FromRequestParts<AppState> for RequireRole {
+impl FromRequestParts<AppState> for RequireVerifiedEmail {
     type Rejection = Response;
 
     async fn from_request_parts(
@@ -83,14 +379,16 @@ impl FromRequestParts<AppState> for RequireRole {
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
         let auth_user = AuthUser::from_request_parts(parts, state).await?;
-        
-        if auth_user.user.role != "admin" {
+
+        if state.settings.email_verification != EmailVerificationPolicy::Optional
+            && !auth_user.user.email_verified
+        {
             return Err((
                 StatusCode::FORBIDDEN,
-                Json(ErrorResponse::new("Admin access required")),
+                Json(ErrorResponse::new("Email verification required")),
             ).into_response());
         }
 
-        Ok(RequireRole)
+        Ok(RequireVerifiedEmail)
     }
 }
\ No newline at end of file
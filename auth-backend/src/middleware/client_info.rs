@@ -0,0 +1,71 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, Extensions, HeaderMap},
+};
+
+use crate::{models::session::ClientInfo, AppState};
+
+/// Longest user agent we keep; anything beyond this is client-controlled noise.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        let ip_address = client_ip(
+            &parts.headers,
+            &parts.extensions,
+            state.settings.trusted_proxy_hops,
+        );
+
+        Ok(ClientInfo { ip_address, user_agent })
+    }
+}
+
+/// The client address. Behind `trusted_proxy_hops` proxies this is the
+/// `X-Forwarded-For` entry appended by the outermost one, counted from the
+/// right; anything further left was written by the client and is ignored.
+/// Without trusted proxies it is the peer address of the connection.
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted_proxy_hops: usize,
+) -> Option<String> {
+    // Forwarding headers are only meaningful behind a proxy that sets them
+    let forwarded_ip = (trusted_proxy_hops > 0)
+        .then(|| {
+            let hops: Vec<&str> = headers
+                .get_all("X-Forwarded-For")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .collect();
+
+            // With fewer hops than proxies, the leftmost was still added by a proxy
+            let index = hops.len().saturating_sub(trusted_proxy_hops);
+            hops.get(index)
+                .map(|ip| ip.to_string())
+                .filter(|ip| !ip.is_empty())
+        })
+        .flatten();
+
+    forwarded_ip.or_else(|| {
+        extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+    })
+}
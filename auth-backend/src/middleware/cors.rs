@@ -1,5 +1,5 @@
 use tower_http::cors::CorsLayer;
-use axum::http::{HeaderValue, Method, HeaderName};
+use axum::http::{header, HeaderValue, Method, HeaderName};
 
 pub fn cors_layer(origin: &str) -> CorsLayer {
     CorsLayer::new()
@@ -11,6 +11,16 @@ pub fn cors_layer(origin: &str) -> CorsLayer {
             HeaderName::from_static("accept"),
             HeaderName::from_static("origin"),
             HeaderName::from_static("x-requested-with"),
+            // Double-submit CSRF token for cookie-authenticated requests
+            HeaderName::from_static("x-csrf-token"),
+        ])
+        // Let browser clients read throttling hints on 429s
+        .expose_headers([
+            header::RETRY_AFTER,
+            HeaderName::from_static("ratelimit-limit"),
+            HeaderName::from_static("ratelimit-remaining"),
+            HeaderName::from_static("ratelimit-reset"),
+            HeaderName::from_static("ratelimit-policy"),
         ])
         .allow_credentials(true)
 }
\ No newline at end of file
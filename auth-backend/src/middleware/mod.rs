@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod client_info;
 pub mod cors;
-pub mod logging;
\ No newline at end of file
+pub mod logging;
+pub mod rate_limit;
\ No newline at end of file
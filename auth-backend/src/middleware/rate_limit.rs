@@ -0,0 +1,276 @@
+//! Token-bucket rate limiting for unauthenticated auth endpoints.
+//!
+//! Every request spends a token from the bucket for its client IP and, when
+//! the JSON body carries an `email`, from the bucket for that email too, so
+//! rotating addresses does not buy extra attempts against one account.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderName, HeaderValue, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tower::{Layer, Service};
+
+use crate::{
+    config::RateLimit,
+    middleware::client_info::client_ip,
+    models::response::ErrorResponse,
+};
+
+/// Request bodies larger than this are not inspected for an email and are rejected.
+const MAX_INSPECTED_BODY_BYTES: usize = 64 * 1024;
+/// Idle buckets are dropped once the table grows past this many keys.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Outcome of spending a token from one bucket.
+struct Decision {
+    allowed: bool,
+    remaining: u32,
+    /// Seconds until one token is available again
+    retry_after: u64,
+    /// Seconds until the bucket is full again
+    reset: u64,
+}
+
+struct RateLimiter {
+    name: &'static str,
+    limit: RateLimit,
+    trusted_proxy_hops: usize,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    fn refill_per_second(&self) -> f64 {
+        self.limit.requests as f64 / self.limit.window_seconds as f64
+    }
+
+    fn check(&self, key: &str) -> Decision {
+        let capacity = self.limit.requests as f64;
+        let rate = self.refill_per_second();
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rate < capacity
+            });
+        }
+
+        let bucket = buckets.entry(format!("{}:{}", self.name, key)).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rate)
+            .min(capacity);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Decision {
+            allowed,
+            remaining: bucket.tokens.floor() as u32,
+            retry_after: ((1.0 - bucket.tokens).max(0.0) / rate).ceil() as u64,
+            reset: ((capacity - bucket.tokens) / rate).ceil() as u64,
+        }
+    }
+}
+
+/// Applies one named limit to the routes it wraps. Each layer keeps its own
+/// buckets, so login attempts do not eat into the refresh budget.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(name: &'static str, limit: RateLimit, trusted_proxy_hops: usize) -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter {
+                name,
+                limit,
+                trusted_proxy_hops,
+                buckets: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            if !limiter.limit.is_enabled() {
+                return inner.call(request).await;
+            }
+
+            let (parts, body) = request.into_parts();
+            let bytes = match to_bytes(body, MAX_INSPECTED_BODY_BYTES).await {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    return Ok((
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        Json(ErrorResponse::new("Request body too large")),
+                    ).into_response());
+                }
+            };
+
+            let mut keys = Vec::new();
+            if let Some(ip) = client_ip(&parts.headers, &parts.extensions, limiter.trusted_proxy_hops) {
+                keys.push(format!("ip:{}", ip));
+            }
+            if let Some(email) = body_email(&bytes) {
+                keys.push(format!("email:{}", email));
+            }
+
+            // The most restrictive bucket decides and is what the headers describe
+            let decision = keys
+                .iter()
+                .map(|key| limiter.check(key))
+                .min_by_key(|decision| (decision.allowed, decision.remaining));
+
+            let request = Request::from_parts(parts, Body::from(bytes));
+
+            let Some(decision) = decision else {
+                return inner.call(request).await;
+            };
+
+            if !decision.allowed {
+                tracing::warn!(
+                    target: "security",
+                    limit = limiter.name,
+                    "Rate limit exceeded"
+                );
+
+                let mut response = (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(ErrorResponse::with_code(
+                        "rate_limited",
+                        "Too many requests. Try again later",
+                        Some(serde_json::json!({ "retry_after": decision.retry_after })),
+                    )),
+                ).into_response();
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(decision.retry_after));
+                set_rate_limit_headers(&mut response, limiter.limit, &decision);
+                return Ok(response);
+            }
+
+            let mut response = inner.call(request).await?;
+            set_rate_limit_headers(&mut response, limiter.limit, &decision);
+            Ok(response)
+        })
+    }
+}
+
+fn body_email(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let email = value.get("email")?.as_str()?.trim().to_lowercase();
+    (!email.is_empty()).then_some(email)
+}
+
+fn set_rate_limit_headers(response: &mut Response, limit: RateLimit, decision: &Decision) {
+    let headers = response.headers_mut();
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(limit.requests));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(decision.reset));
+    if let Ok(policy) = HeaderValue::from_str(&format!("{};w={}", limit.requests, limit.window_seconds)) {
+        headers.insert(RATELIMIT_POLICY, policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::{service_fn, ServiceExt};
+
+    use super::*;
+
+    async fn send(layer: &RateLimitLayer, forwarded_for: &str) -> StatusCode {
+        let service = layer.layer(service_fn(|_: Request<Body>| async {
+            Ok::<_, Infallible>(StatusCode::OK.into_response())
+        }));
+        let request = Request::builder()
+            .header("X-Forwarded-For", forwarded_for)
+            .body(Body::empty())
+            .unwrap();
+
+        service.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn spoofed_leading_hop_does_not_change_the_key() {
+        let layer = RateLimitLayer::new("test", RateLimit::new(1, 60), 1);
+
+        assert_eq!(send(&layer, "1.1.1.1, 203.0.113.7").await, StatusCode::OK);
+        assert_eq!(send(&layer, "2.2.2.2, 203.0.113.7").await, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(send(&layer, "203.0.113.8").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn skips_trusted_hops_from_the_right() {
+        let layer = RateLimitLayer::new("test", RateLimit::new(1, 60), 2);
+
+        assert_eq!(send(&layer, "1.1.1.1, 203.0.113.7, 10.0.0.1").await, StatusCode::OK);
+        assert_eq!(send(&layer, "2.2.2.2, 203.0.113.7, 10.0.0.2").await, StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Prefix that tells personal access tokens apart from JWTs in the
+/// `Authorization` header
+pub const ACCESS_TOKEN_PREFIX: &str = "pat_";
+
+/// What a personal access token may do. `read` covers safe methods and
+/// `write` every other method; `admin` additionally opens the admin routes
+/// to admins' tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateAccessTokenRequest {
+    #[validate(length(min = 1, max = 64, message = "Token name must be between 1 and 64 characters"))]
+    pub name: String,
+
+    #[validate(length(min = 1, message = "Select at least one scope"))]
+    pub scopes: Vec<TokenScope>,
+
+    /// Omit for a token that never expires
+    #[validate(range(min = 1, max = 365, message = "Expiry must be between 1 and 365 days"))]
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub name: String,
+    /// The first characters of the token, to tell tokens apart
+    pub token_prefix: String,
+    pub scopes: Vec<TokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returned once on creation; the plaintext token cannot be retrieved again.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreatedAccessToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub access_token: AccessToken,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AccessTokenRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AccessTokenRow> for AccessToken {
+    fn from(row: AccessTokenRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            token_prefix: row.token_prefix,
+            scopes: row.scopes.split_whitespace().filter_map(TokenScope::from_name).collect(),
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+        }
+    }
+}
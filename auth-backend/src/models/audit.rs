@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    UserRegister,
+    UserLogout,
+    ProfileUpdate,
+    PasswordChange,
+    PasswordReset,
+    AvatarUpdate,
+    AccountDelete,
+    AccessTokenCreate,
+    AccessTokenRevoke,
+    RefreshTokenReuse,
+    AdminRevokeSessions,
+    AdminUnlockUser,
+    AdminRequirePasswordChange,
+    AdminServiceAccountCreate,
+    AdminServiceAccountRotateSecret,
+    AdminServiceAccountDelete,
+    AdminRoleCreate,
+    AdminRoleUpdate,
+    AdminRoleDelete,
+    AdminPermissionCreate,
+    AdminPermissionDelete,
+    AdminSetUserRoles,
+    OrganizationCreate,
+    OrganizationUpdate,
+    OrganizationDelete,
+    OrganizationMemberAdd,
+    OrganizationMemberUpdate,
+    OrganizationMemberRemove,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 28] = [
+        Self::UserRegister,
+        Self::UserLogout,
+        Self::ProfileUpdate,
+        Self::PasswordChange,
+        Self::PasswordReset,
+        Self::AvatarUpdate,
+        Self::AccountDelete,
+        Self::AccessTokenCreate,
+        Self::AccessTokenRevoke,
+        Self::RefreshTokenReuse,
+        Self::AdminRevokeSessions,
+        Self::AdminUnlockUser,
+        Self::AdminRequirePasswordChange,
+        Self::AdminServiceAccountCreate,
+        Self::AdminServiceAccountRotateSecret,
+        Self::AdminServiceAccountDelete,
+        Self::AdminRoleCreate,
+        Self::AdminRoleUpdate,
+        Self::AdminRoleDelete,
+        Self::AdminPermissionCreate,
+        Self::AdminPermissionDelete,
+        Self::AdminSetUserRoles,
+        Self::OrganizationCreate,
+        Self::OrganizationUpdate,
+        Self::OrganizationDelete,
+        Self::OrganizationMemberAdd,
+        Self::OrganizationMemberUpdate,
+        Self::OrganizationMemberRemove,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserRegister => "user.register",
+            Self::UserLogout => "user.logout",
+            Self::ProfileUpdate => "user.profile_update",
+            Self::PasswordChange => "user.password_change",
+            Self::PasswordReset => "user.password_reset",
+            Self::AvatarUpdate => "user.avatar_update",
+            Self::AccountDelete => "user.account_delete",
+            Self::AccessTokenCreate => "user.access_token_create",
+            Self::AccessTokenRevoke => "user.access_token_revoke",
+            Self::RefreshTokenReuse => "session.refresh_token_reuse",
+            Self::AdminRevokeSessions => "admin.revoke_sessions",
+            Self::AdminUnlockUser => "admin.unlock_user",
+            Self::AdminRequirePasswordChange => "admin.require_password_change",
+            Self::AdminServiceAccountCreate => "admin.service_account_create",
+            Self::AdminServiceAccountRotateSecret => "admin.service_account_rotate_secret",
+            Self::AdminServiceAccountDelete => "admin.service_account_delete",
+            Self::AdminRoleCreate => "admin.role_create",
+            Self::AdminRoleUpdate => "admin.role_update",
+            Self::AdminRoleDelete => "admin.role_delete",
+            Self::AdminPermissionCreate => "admin.permission_create",
+            Self::AdminPermissionDelete => "admin.permission_delete",
+            Self::AdminSetUserRoles => "admin.set_user_roles",
+            Self::OrganizationCreate => "organization.create",
+            Self::OrganizationUpdate => "organization.update",
+            Self::OrganizationDelete => "organization.delete",
+            Self::OrganizationMemberAdd => "organization.member_add",
+            Self::OrganizationMemberUpdate => "organization.member_update",
+            Self::OrganizationMemberRemove => "organization.member_remove",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_str() == name)
+    }
+
+    /// Human readable form shown in the dashboard activity feed.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::UserRegister => "User registration",
+            Self::UserLogout => "User logout",
+            Self::ProfileUpdate => "Profile update",
+            Self::PasswordChange => "Password change",
+            Self::PasswordReset => "Password reset",
+            Self::AvatarUpdate => "Avatar update",
+            Self::AccountDelete => "Account deletion",
+            Self::AccessTokenCreate => "Personal access token created",
+            Self::AccessTokenRevoke => "Personal access token revoked",
+            Self::RefreshTokenReuse => "Refresh token reuse detected",
+            Self::AdminRevokeSessions => "Sessions revoked by admin",
+            Self::AdminUnlockUser => "Account unlocked by admin",
+            Self::AdminRequirePasswordChange => "Password change required by admin",
+            Self::AdminServiceAccountCreate => "Service account created",
+            Self::AdminServiceAccountRotateSecret => "Service account secret rotated",
+            Self::AdminServiceAccountDelete => "Service account deleted",
+            Self::AdminRoleCreate => "Role created",
+            Self::AdminRoleUpdate => "Role updated",
+            Self::AdminRoleDelete => "Role deleted",
+            Self::AdminPermissionCreate => "Permission created",
+            Self::AdminPermissionDelete => "Permission deleted",
+            Self::AdminSetUserRoles => "User roles changed by admin",
+            Self::OrganizationCreate => "Organization created",
+            Self::OrganizationUpdate => "Organization updated",
+            Self::OrganizationDelete => "Organization deleted",
+            Self::OrganizationMemberAdd => "Organization member added",
+            Self::OrganizationMemberUpdate => "Organization member role changed",
+            Self::OrganizationMemberRemove => "Organization member removed",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    /// Copied at write time so the event stays readable after the account is deleted
+    pub actor_email: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub action: String,
+    pub ip_address: Option<String>,
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AuditEventRow {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_email: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub action: String,
+    pub ip_address: Option<String>,
+    pub metadata: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEventRow> for AuditEvent {
+    fn from(row: AuditEventRow) -> Self {
+        Self {
+            id: row.id,
+            actor_id: row.actor_id,
+            actor_email: row.actor_email,
+            target_id: row.target_id,
+            action: row.action,
+            ip_address: row.ip_address,
+            metadata: serde_json::from_str(&row.metadata).unwrap_or_default(),
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// Only events performed by this user
+    pub actor_id: Option<Uuid>,
+    /// Only events affecting this user
+    pub target_id: Option<Uuid>,
+    /// Exact action name, e.g. `user.password_change`
+    pub action: Option<String>,
+    /// Inclusive lower bound (RFC 3339)
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound (RFC 3339)
+    pub to: Option<DateTime<Utc>>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = 200, message = "Limit must be between 1 and 200"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditEventPage {
+    pub events: Vec<AuditEvent>,
+    /// Pass as `cursor` to fetch the next (older) page; absent on the last page
+    pub next_cursor: Option<String>,
+}
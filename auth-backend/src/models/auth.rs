@@ -4,14 +4,17 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::user::User;
+use super::{
+    organization::OrgRole,
+    user::{PasswordChangeReason, User},
+};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct RegisterRequest {
     #[validate(email(message = "Please enter a valid email address"))]
     pub email: String,
     
-    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    /// Checked against the password policy by the handler
     pub password: String,
     
     #[validate(must_match(other = "password", message = "Passwords do not match"))]
@@ -30,27 +33,109 @@ pub struct LoginRequest {
     pub password: String,
     
     #[serde(default)]
+    /// `false` issues a short-lived, session-scoped refresh token; `true` a long-lived one
     pub remember_me: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AuthResponse {
     pub user: User,
+    /// Omitted in cookie mode (`AUTH_COOKIES`), where it is set as an HttpOnly cookie instead
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub token: String,
+    /// Omitted in cookie mode, like `token`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub refresh_token: String,
     pub expires_in: i64,
+    /// Set when every route except `PUT /api/users/password` will reject the
+    /// token (code `password_change_required`) until the password is changed
+    pub password_change_required: Option<PasswordChangeReason>,
+    /// Whether the refresh token outlives the browser session; sets the cookie lifetimes
+    #[serde(skip)]
+    pub persistent: bool,
+}
+
+/// Returned by login in place of an `AuthResponse` when the account has a
+/// second factor enabled. Exchange it via `POST /api/auth/mfa/verify`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired(MfaChallenge),
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RefreshRequest {
+    /// May be omitted in cookie mode, where the refresh token cookie is used
+    #[serde(default)]
     pub refresh_token: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Verification token is required"))]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email(message = "Please enter a valid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Please enter a valid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "Reset token is required"))]
+    pub token: String,
+
+    /// Checked against the password policy once the token identifies the user
+    pub new_password: String,
+
+    #[validate(must_match(other = "new_password", message = "Passwords do not match"))]
+    pub confirm_password: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String, // user id
+    pub sub: String, // user id, or the client id for client credentials tokens
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sid: String, // session id
+    pub jti: String, // token id, checked against the revocation list
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub role: String,
+    /// Every role the user holds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Permissions granted by those roles, as of when the token was issued
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    /// The organization the session acts in, if the user belongs to any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    /// The user's role in that organization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_role: Option<OrgRole>,
+    /// Set on client credentials tokens, which identify a service account rather than a user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Space separated scopes granted to the client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
     pub exp: usize,
     pub iat: usize,
 }
@@ -58,6 +143,18 @@ pub struct Claims {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RefreshClaims {
     pub sub: String, // user id
+    pub jti: String, // refresh_tokens row id, keeps tokens issued in the same second distinct
+    pub exp: usize,
+    pub iat: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub sub: String, // user id
+    pub jti: String, // mfa_challenges id
+    pub aud: String,
+    #[serde(default)]
+    pub remember_me: bool,
     pub exp: usize,
     pub iat: usize,
 }
@@ -69,6 +166,35 @@ pub struct RefreshToken {
     pub token_hash: String,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub persistent: bool,
+    pub family_id: Uuid,
+    pub used_at: Option<DateTime<Utc>>,
+    pub access_jti: Option<Uuid>,
+}
+
+/// A public access-token verification key, as published at `/.well-known/jwks.json`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKey {
+    pub kty: String,
+    pub kid: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
 }
 
 fn validate_terms_accepted(value: &bool) -> Result<(), validator::ValidationError> {
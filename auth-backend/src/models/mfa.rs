@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TotpCodeRequest {
+    #[validate(length(min = 6, max = 6, message = "Authentication code must be 6 digits"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MfaCodeRequest {
+    /// A 6-digit TOTP code or one of the user's recovery codes
+    #[validate(length(min = 1, message = "Authentication code is required"))]
+    pub code: String,
+}
+
+/// Plaintext recovery codes. Only returned once, when the set is generated.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodeStatus {
+    pub total: i64,
+    pub remaining: i64,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MfaVerifyRequest {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    pub mfa_token: String,
+
+    /// A 6-digit TOTP code or one of the user's recovery codes
+    #[validate(length(min = 1, message = "Authentication code is required"))]
+    pub code: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct UserTotpRow {
+    pub secret_encrypted: String,
+    pub confirmed: bool,
+    pub last_used_step: Option<i64>,
+}
@@ -1,7 +1,23 @@
+pub mod access_token;
+pub mod audit;
 pub mod auth;
+pub mod mfa;
+pub mod organization;
+pub mod passkey;
+pub mod role;
+pub mod service_account;
+pub mod session;
 pub mod user;
 pub mod response;
 
+pub use access_token::*;
+pub use audit::*;
 pub use auth::*;
+pub use mfa::*;
+pub use organization::*;
+pub use passkey::*;
+pub use role::*;
+pub use service_account::*;
+pub use session::*;
 pub use user::*;
 pub use response::*;
\ No newline at end of file
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A member's role within one organization. Owners can do everything admins
+/// can, and also delete the organization and manage other owners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Member => "member",
+            Self::Admin => "admin",
+            Self::Owner => "owner",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "member" => Some(Self::Member),
+            "admin" => Some(Self::Admin),
+            "owner" => Some(Self::Owner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An organization as seen by one of its members.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserOrganization {
+    #[serde(flatten)]
+    pub organization: Organization,
+    pub role: OrgRole,
+    /// Whether this is the organization the current session acts in
+    pub active: bool,
+}
+
+/// The caller's membership in their active organization.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Membership {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: OrgRole,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct MembershipRow {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+impl TryFrom<MembershipRow> for Membership {
+    type Error = anyhow::Error;
+
+    fn try_from(row: MembershipRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            organization_id: row.organization_id,
+            user_id: row.user_id,
+            role: OrgRole::from_name(&row.role)
+                .ok_or_else(|| anyhow::anyhow!("Unknown organization role: {}", row.role))?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct UserOrganizationRow {
+    #[sqlx(flatten)]
+    pub organization: Organization,
+    pub role: String,
+}
+
+impl UserOrganizationRow {
+    pub fn into_user_organization(self, active_organization_id: Option<Uuid>) -> anyhow::Result<UserOrganization> {
+        Ok(UserOrganization {
+            active: active_organization_id == Some(self.organization.id),
+            organization: self.organization,
+            role: OrgRole::from_name(&self.role)
+                .ok_or_else(|| anyhow::anyhow!("Unknown organization role: {}", self.role))?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: OrgRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct OrganizationMemberRow {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<OrganizationMemberRow> for OrganizationMember {
+    type Error = anyhow::Error;
+
+    fn try_from(row: OrganizationMemberRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user_id: row.user_id,
+            email: row.email,
+            name: row.name,
+            role: OrgRole::from_name(&row.role)
+                .ok_or_else(|| anyhow::anyhow!("Unknown organization role: {}", row.role))?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateOrganizationRequest {
+    #[validate(length(min = 1, max = 128, message = "Name must be between 1 and 128 characters"))]
+    pub name: String,
+
+    /// Unique URL-friendly identifier, e.g. `acme-corp`
+    #[validate(custom(function = "validate_slug", message = "Slugs are 3 to 64 lowercase letters, digits and -"))]
+    pub slug: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UpdateOrganizationRequest {
+    #[validate(length(min = 1, max = 128, message = "Name must be between 1 and 128 characters"))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AddMemberRequest {
+    /// Email of an existing account
+    #[validate(email(message = "Please enter a valid email address"))]
+    pub email: String,
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateMemberRequest {
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SwitchOrganizationRequest {
+    pub organization_id: Uuid,
+}
+
+fn validate_slug(slug: &str) -> Result<(), validator::ValidationError> {
+    let valid = (3..=64).contains(&slug.len())
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("invalid_slug"))
+    }
+}
@@ -11,6 +11,9 @@ pub struct ApiResponse<T> {
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResponse {
     pub success: bool,
+    /// Machine-readable reason for errors clients are expected to handle specifically
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
     pub error: String,
     pub details: Option<serde_json::Value>,
 }
@@ -21,6 +24,7 @@ pub struct DashboardStats {
     pub active_sessions: i64,
     pub new_registrations_today: i64,
     pub login_attempts_today: i64,
+    pub failed_logins_today: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -46,6 +50,7 @@ impl ErrorResponse {
     pub fn new(error: impl Into<String>) -> Self {
         Self {
             success: false,
+            code: None,
             error: error.into(),
             details: None,
         }
@@ -54,8 +59,18 @@ impl ErrorResponse {
     pub fn with_details(error: impl Into<String>, details: serde_json::Value) -> Self {
         Self {
             success: false,
+            code: None,
             error: error.into(),
             details: Some(details),
         }
     }
+
+    pub fn with_code(code: impl Into<String>, error: impl Into<String>, details: Option<serde_json::Value>) -> Self {
+        Self {
+            success: false,
+            code: Some(code.into()),
+            error: error.into(),
+            details,
+        }
+    }
 }
\ No newline at end of file
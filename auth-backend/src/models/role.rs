@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Role every new account is given
+pub const DEFAULT_ROLE: &str = "user";
+
+/// Built-in role holding every built-in permission; its permissions cannot be changed
+pub const ADMIN_ROLE: &str = "admin";
+
+/// A permission a handler can require with `RequirePermission<P>`. The name
+/// must match a row in `permissions`.
+pub trait Permission: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+/// Permissions checked by this API, seeded as built-in permissions.
+pub mod permissions {
+    use super::Permission;
+
+    macro_rules! permission {
+        ($(#[$doc:meta])* $ty:ident => $name:literal) => {
+            $(#[$doc])*
+            pub struct $ty;
+
+            impl Permission for $ty {
+                const NAME: &'static str = $name;
+            }
+        };
+    }
+
+    permission!(
+        /// View dashboard statistics and recent activity
+        DashboardRead => "dashboard:read"
+    );
+    permission!(
+        /// View login analytics
+        AnalyticsRead => "analytics:read"
+    );
+    permission!(
+        /// List users and their roles
+        UsersRead => "users:read"
+    );
+    permission!(
+        /// Sign users out, unlock them and require password changes
+        UsersManage => "users:manage"
+    );
+    permission!(
+        /// Query the audit log
+        AuditRead => "audit:read"
+    );
+    permission!(
+        /// Create, rotate and delete service accounts
+        ServiceAccountsManage => "service_accounts:manage"
+    );
+    permission!(
+        /// Manage roles and permissions and assign roles to users
+        RolesManage => "roles:manage"
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Built-in roles cannot be renamed or deleted
+    pub built_in: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct RoleRow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub built_in: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RoleRow {
+    pub fn into_role(self, permissions: Vec<String>) -> Role {
+        Role {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            built_in: self.built_in,
+            permissions,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct PermissionDefinition {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Built-in permissions are checked by this API and cannot be deleted
+    pub built_in: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateRoleRequest {
+    #[validate(custom(function = "validate_role_name", message = "Role names are 1 to 64 lowercase letters, digits, _ and -"))]
+    pub name: String,
+
+    #[validate(length(max = 256, message = "Description must be at most 256 characters"))]
+    pub description: Option<String>,
+
+    /// Permission names, e.g. `users:read`
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// Replaces the role's description and permissions.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UpdateRoleRequest {
+    #[validate(length(max = 256, message = "Description must be at most 256 characters"))]
+    pub description: Option<String>,
+
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreatePermissionRequest {
+    #[validate(custom(function = "validate_permission_name", message = "Permission names are 1 to 64 lowercase letters, digits, _ - . and :"))]
+    pub name: String,
+
+    #[validate(length(max = 256, message = "Description must be at most 256 characters"))]
+    pub description: Option<String>,
+}
+
+/// Replaces every role the user holds.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct SetUserRolesRequest {
+    #[validate(length(min = 1, message = "Assign at least one role"))]
+    pub roles: Vec<String>,
+}
+
+/// The roles a user holds and the permissions they grant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct UserRoles {
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl UserRoles {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+    }
+}
+
+fn validate_role_name(name: &str) -> Result<(), validator::ValidationError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("invalid_role_name"))
+    }
+}
+
+fn validate_permission_name(name: &str) -> Result<(), validator::ValidationError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.' | ':'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("invalid_permission_name"))
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Prefix of generated client ids, so they are recognisable in logs and config
+pub const CLIENT_ID_PREFIX: &str = "svc_";
+
+pub const GRANT_TYPE_CLIENT_CREDENTIALS: &str = "client_credentials";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateServiceAccountRequest {
+    #[validate(length(min = 1, max = 64, message = "Name must be between 1 and 64 characters"))]
+    pub name: String,
+
+    #[validate(length(max = 256, message = "Description must be at most 256 characters"))]
+    pub description: Option<String>,
+
+    /// Scopes the client may request, e.g. `billing:read`
+    #[validate(length(min = 1, max = 32, message = "Between 1 and 32 scopes are required"))]
+    #[validate(custom(function = "validate_scopes", message = "Scopes may only contain letters, digits and _ - . : /"))]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceAccount {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<Uuid>,
+    /// When the client last obtained a token
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Returned on creation and secret rotation; the secret cannot be retrieved again.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceAccountCredentials {
+    pub client_secret: String,
+    #[serde(flatten)]
+    pub service_account: ServiceAccount,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ServiceAccountRow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub client_id: String,
+    pub client_secret_hash: String,
+    pub scopes: String,
+    pub created_by: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ServiceAccountRow> for ServiceAccount {
+    fn from(row: ServiceAccountRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            client_id: row.client_id,
+            scopes: row.scopes.split_whitespace().map(str::to_string).collect(),
+            created_by: row.created_by,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// `POST /oauth/token` form body (RFC 6749 section 4.4). The client may
+/// authenticate with HTTP Basic instead of `client_id` and `client_secret`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    /// Space separated subset of the client's scopes; omit for all of them
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+/// Error body defined by RFC 6749 section 5.2.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthError {
+    pub error: String,
+    pub error_description: String,
+}
+
+impl OAuthError {
+    pub fn new(error: impl Into<String>, error_description: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            error_description: error_description.into(),
+        }
+    }
+}
+
+/// Scope tokens as allowed by RFC 6749, narrowed to a readable set.
+pub fn is_valid_scope(scope: &str) -> bool {
+    !scope.is_empty()
+        && scope.len() <= 64
+        && scope
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/'))
+}
+
+fn validate_scopes(scopes: &[String]) -> Result<(), validator::ValidationError> {
+    if scopes.iter().all(|scope| is_valid_scope(scope)) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("invalid_scope"))
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -16,6 +16,32 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    /// Set by an admin; cleared once the user changes their password
+    pub must_change_password: bool,
+}
+
+impl User {
+    /// Why the user has to change their password before using any other route,
+    /// if they do. `max_age_days` of 0 disables expiry.
+    pub fn password_change_reason(&self, max_age_days: i64) -> Option<PasswordChangeReason> {
+        if self.must_change_password {
+            return Some(PasswordChangeReason::Required);
+        }
+
+        let changed_at = self.password_changed_at.unwrap_or(self.created_at);
+        (max_age_days > 0 && changed_at + Duration::days(max_age_days) <= Utc::now())
+            .then_some(PasswordChangeReason::Expired)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordChangeReason {
+    /// An admin flagged the account
+    Required,
+    /// The password is older than `PASSWORD_MAX_AGE_DAYS`
+    Expired,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -43,7 +69,7 @@ pub struct ChangePasswordRequest {
     #[validate(length(min = 1, message = "Current password is required"))]
     pub current_password: String,
     
-    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    /// Checked against the password policy by the handler
     pub new_password: String,
     
     #[validate(must_match(other = "new_password", message = "Passwords do not match"))]
@@ -63,6 +89,8 @@ pub struct UserRow {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub must_change_password: bool,
 }
 
 impl From<UserRow> for User {
@@ -78,6 +106,8 @@ impl From<UserRow> for User {
             created_at: row.created_at,
             updated_at: row.updated_at,
             last_login: row.last_login,
+            password_changed_at: row.password_changed_at,
+            must_change_password: row.must_change_password,
         }
     }
 }
@@ -0,0 +1,135 @@
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use tower_http::services::ServeDir;
+
+use crate::{
+    config::Settings,
+    handlers::{
+        access_token, admin, analytics, auth, metrics, mfa, oauth, organization, passkey, role, service_account,
+        session, user,
+    },
+    middleware::{cors::cors_layer, logging::logging_layer, rate_limit::RateLimitLayer},
+    AppState,
+};
+
+/// The full application: API routes, OAuth2 token endpoint, JWKS, metrics and
+/// uploads, with CORS and request logging.
+pub fn app(state: AppState) -> Router {
+    let settings = state.settings.clone();
+
+    Router::new()
+        // API routes
+        .nest("/api", api_routes(&settings))
+
+        // OAuth2 token endpoint for service accounts
+        .route(
+            "/oauth/token",
+            post(oauth::token).layer(RateLimitLayer::new(
+                "token",
+                settings.rate_limit_token,
+                settings.trusted_proxy_hops,
+            )),
+        )
+
+        // Public keys for verifying access tokens
+        .route("/.well-known/jwks.json", get(auth::jwks))
+
+        // Prometheus scrape endpoint, behind METRICS_TOKEN
+        .route("/metrics", get(metrics::metrics))
+
+        // Static file serving for avatars
+        .nest_service("/uploads", ServeDir::new("uploads"))
+
+        // Add middleware
+        .layer(cors_layer(&settings.cors_origin))
+        .layer(logging_layer())
+
+        // Add application state
+        .with_state(state)
+}
+
+fn api_routes(settings: &Settings) -> Router<AppState> {
+    let trust_proxy = settings.trusted_proxy_hops;
+    let login_limit = RateLimitLayer::new("login", settings.rate_limit_login, trust_proxy);
+    let register_limit = RateLimitLayer::new("register", settings.rate_limit_register, trust_proxy);
+    let refresh_limit = RateLimitLayer::new("refresh", settings.rate_limit_refresh, trust_proxy);
+    let mfa_limit = RateLimitLayer::new("mfa", settings.rate_limit_mfa, trust_proxy);
+    let email_limit = RateLimitLayer::new("email", settings.rate_limit_email, trust_proxy);
+
+    Router::new()
+        // Authentication routes
+        .route("/auth/register", post(auth::register).layer(register_limit))
+        .route("/auth/login", post(auth::login).layer(login_limit.clone()))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/refresh", post(auth::refresh_token).layer(refresh_limit))
+        .route("/auth/verify-email", post(auth::verify_email).layer(email_limit.clone()))
+        .route("/auth/resend-verification", post(auth::resend_verification).layer(email_limit.clone()))
+        .route("/auth/forgot-password", post(auth::forgot_password).layer(email_limit.clone()))
+        .route("/auth/reset-password", post(auth::reset_password).layer(email_limit))
+        .route("/auth/me", get(auth::get_current_user))
+        .route("/auth/switch-org", post(auth::switch_organization))
+        .route("/auth/mfa/verify", post(mfa::verify_mfa).layer(mfa_limit))
+        .route("/auth/passkeys/login/start", post(passkey::start_login).layer(login_limit.clone()))
+        .route("/auth/passkeys/login/finish", post(passkey::finish_login).layer(login_limit))
+        
+        // User routes
+        .route("/users/profile", get(user::get_profile))
+        .route("/users/profile", put(user::update_profile))
+        .route("/users/password", put(user::change_password))
+        .route("/users/account", delete(user::delete_account))
+        .route("/users/avatar", post(user::upload_avatar))
+        .route("/users/mfa/totp/enroll", post(mfa::enroll_totp))
+        .route("/users/mfa/totp/confirm", post(mfa::confirm_totp))
+        .route("/users/mfa/totp/disable", post(mfa::disable_totp))
+        .route("/users/mfa/recovery-codes", get(mfa::get_recovery_code_status))
+        .route("/users/mfa/recovery-codes", post(mfa::regenerate_recovery_codes))
+        .route("/users/passkeys", get(passkey::list_passkeys))
+        .route("/users/passkeys/register/start", post(passkey::start_registration))
+        .route("/users/passkeys/register/finish", post(passkey::finish_registration))
+        .route("/users/passkeys/:id", put(passkey::rename_passkey))
+        .route("/users/passkeys/:id", delete(passkey::delete_passkey))
+        .route("/users/sessions", get(session::list_sessions))
+        .route("/users/sessions/revoke-others", post(session::revoke_other_sessions))
+        .route("/users/sessions/:id", delete(session::revoke_session))
+        .route("/users/tokens", get(access_token::list_tokens))
+        .route("/users/tokens", post(access_token::create_token))
+        .route("/users/tokens/:id", delete(access_token::revoke_token))
+
+        // Organization routes
+        .route("/organizations", get(organization::list_organizations))
+        .route("/organizations", post(organization::create_organization))
+        .route("/organizations/:id", get(organization::get_organization))
+        .route("/organizations/:id", put(organization::update_organization))
+        .route("/organizations/:id", delete(organization::delete_organization))
+        .route("/organizations/:id/members", get(organization::list_members))
+        .route("/organizations/:id/members", post(organization::add_member))
+        .route("/organizations/:id/members/:user_id", put(organization::update_member))
+        .route("/organizations/:id/members/:user_id", delete(organization::remove_member))
+        
+        // Admin routes
+        .route("/admin/dashboard/stats", get(admin::get_dashboard_stats))
+        .route("/admin/dashboard/activity", get(admin::get_recent_activity))
+        .route("/admin/users", get(admin::list_users))
+        .route("/admin/users/:id/revoke-sessions", post(admin::revoke_user_sessions))
+        .route("/admin/users/:id/unlock", post(admin::unlock_user))
+        .route("/admin/users/:id/require-password-change", post(admin::require_password_change))
+        .route("/admin/users/:id/roles", get(role::get_user_roles))
+        .route("/admin/users/:id/roles", put(role::set_user_roles))
+        .route("/admin/audit", get(admin::list_audit_events))
+        .route("/admin/roles", get(role::list_roles))
+        .route("/admin/roles", post(role::create_role))
+        .route("/admin/roles/:id", put(role::update_role))
+        .route("/admin/roles/:id", delete(role::delete_role))
+        .route("/admin/permissions", get(role::list_permissions))
+        .route("/admin/permissions", post(role::create_permission))
+        .route("/admin/permissions/:id", delete(role::delete_permission))
+        .route("/admin/service-accounts", get(service_account::list_service_accounts))
+        .route("/admin/service-accounts", post(service_account::create_service_account))
+        .route("/admin/service-accounts/:id/rotate-secret", post(service_account::rotate_service_account_secret))
+        .route("/admin/service-accounts/:id", delete(service_account::delete_service_account))
+        
+        // Analytics routes
+        .route("/analytics/logins-per-day", get(analytics::logins_per_day))
+}
\ No newline at end of file
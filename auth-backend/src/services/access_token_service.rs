@@ -0,0 +1,193 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        access_token::{
+            AccessToken, AccessTokenRow, CreateAccessTokenRequest, CreatedAccessToken, TokenScope,
+            ACCESS_TOKEN_PREFIX,
+        },
+        audit::AuditAction,
+        session::ClientInfo,
+    },
+    services::audit_service::{AuditService, NewAuditEvent},
+    utils::password::{generate_token, hash_token},
+};
+
+/// Characters of the token kept in plaintext so users can tell tokens apart
+const DISPLAYED_PREFIX_LENGTH: usize = ACCESS_TOKEN_PREFIX.len() + 8;
+
+/// `last_used_at` is only rewritten once it is this stale, so authenticated
+/// requests do not each cost a database write
+const LAST_USED_RESOLUTION_SECONDS: i64 = 60;
+
+/// A personal access token that passed verification.
+#[derive(Debug, Clone)]
+pub struct AccessTokenGrant {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<TokenScope>,
+}
+
+pub struct AccessTokenService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AccessTokenService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates a token and returns its plaintext, which is not stored.
+    pub async fn create_token(
+        &self,
+        user_id: Uuid,
+        request: CreateAccessTokenRequest,
+        client: &ClientInfo,
+    ) -> Result<CreatedAccessToken> {
+        let token = format!("{}{}", ACCESS_TOKEN_PREFIX, generate_token());
+        let now = Utc::now();
+
+        let mut scopes = request.scopes;
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+
+        let access_token = AccessToken {
+            id: Uuid::new_v4(),
+            name: request.name,
+            token_prefix: token[..DISPLAYED_PREFIX_LENGTH].to_string(),
+            scopes,
+            expires_at: request.expires_in_days.map(|days| now + Duration::days(days.into())),
+            last_used_at: None,
+            created_at: now,
+        };
+
+        let scope_names: Vec<&str> = access_token.scopes.iter().map(TokenScope::as_str).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO personal_access_tokens
+                (id, user_id, name, token_hash, token_prefix, scopes, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(access_token.id)
+        .bind(user_id)
+        .bind(&access_token.name)
+        .bind(hash_token(&token))
+        .bind(&access_token.token_prefix)
+        .bind(scope_names.join(" "))
+        .bind(access_token.expires_at)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::AccessTokenCreate,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({
+                    "token_id": access_token.id,
+                    "name": access_token.name,
+                    "scopes": scope_names,
+                }),
+            })
+            .await?;
+
+        Ok(CreatedAccessToken { token, access_token })
+    }
+
+    pub async fn list_tokens(&self, user_id: Uuid) -> Result<Vec<AccessToken>> {
+        let rows = sqlx::query_as::<_, AccessTokenRow>(
+            r#"
+            SELECT id, user_id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+            FROM personal_access_tokens
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(AccessToken::from).collect())
+    }
+
+    pub async fn revoke_token(&self, user_id: Uuid, token_id: Uuid, client: &ClientInfo) -> Result<()> {
+        let result = sqlx::query("DELETE FROM personal_access_tokens WHERE id = ? AND user_id = ?")
+            .bind(token_id)
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Access token not found"));
+        }
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::AccessTokenRevoke,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "token_id": token_id }),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Looks the token up by its digest and records the use, to the minute.
+    pub async fn authenticate(&self, token: &str) -> Result<AccessTokenGrant> {
+        let row = sqlx::query_as::<_, AccessTokenRow>(
+            r#"
+            SELECT id, user_id, name, token_prefix, scopes, expires_at, last_used_at, created_at
+            FROM personal_access_tokens
+            WHERE token_hash = ?
+            "#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Invalid access token"))?;
+
+        let now = Utc::now();
+        if row.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(anyhow!("Access token has expired"));
+        }
+
+        let stale_before = now - Duration::seconds(LAST_USED_RESOLUTION_SECONDS);
+        if row.last_used_at.is_none_or(|last_used_at| last_used_at <= stale_before) {
+            sqlx::query("UPDATE personal_access_tokens SET last_used_at = ? WHERE id = ?")
+                .bind(now)
+                .bind(row.id)
+                .execute(self.pool)
+                .await?;
+        }
+
+        let user_id = row.user_id;
+        let access_token = AccessToken::from(row);
+        Ok(AccessTokenGrant {
+            id: access_token.id,
+            user_id,
+            scopes: access_token.scopes,
+        })
+    }
+}
+
+/// Deletes every personal access token of the user. Takes an executor so the
+/// password and sign-out paths can run it inside their own transaction.
+pub async fn delete_user_access_tokens<'e, E>(executor: E, user_id: Uuid) -> Result<u64>
+where
+    E: SqliteExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM personal_access_tokens WHERE user_id = ?")
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
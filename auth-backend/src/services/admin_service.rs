@@ -4,7 +4,10 @@ use sqlx::SqlitePool;
 use serde::Serialize;
 use uuid::Uuid;
 
-use crate::models::response::{DashboardStats, ActivityItem};
+use crate::models::{
+    audit::AuditAction,
+    response::{DashboardStats, ActivityItem},
+};
 
 pub struct AdminService<'a> {
     pool: &'a SqlitePool,
@@ -14,6 +17,7 @@ pub struct AdminService<'a> {
 pub struct LoginsPerDay {
     pub date: String,
     pub logins: i64,
+    pub failed_logins: i64,
 }
 
 impl<'a> AdminService<'a> {
@@ -27,11 +31,17 @@ impl<'a> AdminService<'a> {
             .fetch_one(self.pool)
             .await?;
 
-        // Get active sessions (refresh tokens created in last 10 minutes)
-        let ten_minutes_ago = Utc::now() - Duration::minutes(10);
+        // Sessions that can still be refreshed, as in each user's session list
+        let now = Utc::now();
         let active_sessions: i32 = sqlx::query_scalar!(
-            "SELECT COUNT(DISTINCT user_id) FROM refresh_tokens WHERE created_at > ?",
-            ten_minutes_ago
+            r#"
+            SELECT COUNT(*) FROM sessions s
+            WHERE EXISTS (
+                SELECT 1 FROM refresh_tokens rt
+                WHERE rt.family_id = s.id AND rt.used_at IS NULL AND rt.expires_at > ?
+            )
+            "#,
+            now
         )
         .fetch_one(self.pool)
         .await?;
@@ -45,9 +55,16 @@ impl<'a> AdminService<'a> {
         .fetch_one(self.pool)
         .await?;
 
-        // Get login attempts today (approximate by counting refresh tokens created today)
-        let login_attempts_today: i32 = sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM refresh_tokens WHERE DATE(created_at) = ?",
+        // Get login attempts today. A password step that only led to an MFA challenge
+        // is not counted, since the second step records the attempt's outcome.
+        let login_attempts = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) as "total!: i64",
+                COALESCE(SUM(outcome = 'failure'), 0) as "failed!: i64"
+            FROM login_events
+            WHERE DATE(created_at) = ? AND outcome != 'mfa_required'
+            "#,
             today
         )
         .fetch_one(self.pool)
@@ -57,76 +74,59 @@ impl<'a> AdminService<'a> {
             total_users: total_users.into(),
             active_sessions: active_sessions.into(),
             new_registrations_today: new_registrations_today.into(),
-            login_attempts_today: login_attempts_today.into(),
+            login_attempts_today: login_attempts.total,
+            failed_logins_today: login_attempts.failed,
         })
     }
 
+    /// The latest audit events, labelled for the dashboard feed.
     pub async fn get_recent_activity(&self) -> Result<Vec<ActivityItem>> {
-        // Get recent user registrations
-        let registrations = sqlx::query!(
-            r#"
-            SELECT id, email, created_at, 'User registration' as action, 'success' as status
-            FROM users 
-            ORDER BY created_at DESC 
-            LIMIT 10
-            "#
-        )
-        .fetch_all(self.pool)
-        .await?;
-
-        // Get recent login attempts (refresh token creations)
-        let logins = sqlx::query!(
+        let events = sqlx::query!(
             r#"
-            SELECT rt.id, u.email, rt.created_at, 'User login' as action, 'success' as status
-            FROM refresh_tokens rt
-            JOIN users u ON rt.user_id = u.id
-            ORDER BY rt.created_at DESC
-            LIMIT 10
+            SELECT
+                ae.id as "id!: Uuid",
+                COALESCE(ae.actor_email, u.email, 'unknown') as "email!: String",
+                ae.action,
+                ae.created_at as "created_at!: DateTime<Utc>"
+            FROM audit_events ae
+            LEFT JOIN users u ON ae.target_id = u.id
+            ORDER BY ae.created_at DESC, ae.id DESC
+            LIMIT 20
             "#
         )
         .fetch_all(self.pool)
         .await?;
 
-        let mut activities = Vec::new();
-
-        // Add registrations
-        for reg in registrations {
-            activities.push(ActivityItem {
-                id: Uuid::from_slice(&reg.id.expect("User ID should not be null")).unwrap().to_string(),
-                action: reg.action,
-                user_email: reg.email,
-                timestamp: DateTime::parse_from_rfc3339(&reg.created_at)?.with_timezone(&Utc),
-                status: reg.status,
-            });
-        }
-
-        // Add logins
-        for login in logins {
-            activities.push(ActivityItem {
-                id: Uuid::from_slice(&login.id.expect("Refresh token ID should not be null")).unwrap().to_string(),
-                action: login.action,
-                user_email: login.email,
-                timestamp: DateTime::parse_from_rfc3339(&login.created_at)?.with_timezone(&Utc),
-                status: login.status,
-            });
-        }
-
-        // Sort by timestamp descending and take top 20
-        activities.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        activities.truncate(20);
+        let activities = events
+            .into_iter()
+            .map(|event| ActivityItem {
+                id: event.id.to_string(),
+                action: AuditAction::from_name(&event.action)
+                    .map(|action| action.label().to_string())
+                    .unwrap_or(event.action),
+                user_email: event.email,
+                timestamp: event.created_at,
+                status: "success".to_string(),
+            })
+            .collect();
 
         Ok(activities)
     }
 
     pub async fn get_logins_per_day(&self) -> Result<Vec<LoginsPerDay>> {
+        let since = (Utc::now() - Duration::days(6)).date_naive();
         let rows = sqlx::query!(
             r#"
-            SELECT strftime('%w', created_at) as weekday, COUNT(*) as logins
-            FROM refresh_tokens
-            WHERE created_at >= datetime('now', '-6 days')
-            GROUP BY weekday
-            ORDER BY weekday
-            "#
+            SELECT
+                strftime('%w', created_at) as weekday,
+                COALESCE(SUM(outcome = 'success'), 0) as "logins!: i64",
+                COALESCE(SUM(outcome = 'failure'), 0) as "failed_logins!: i64"
+            FROM login_events
+            WHERE DATE(created_at) >= ?
+            GROUP BY DATE(created_at)
+            ORDER BY DATE(created_at)
+            "#,
+            since
         )
         .fetch_all(self.pool)
         .await?;
@@ -137,7 +137,8 @@ impl<'a> AdminService<'a> {
             let idx: usize = row.weekday.as_ref().unwrap().parse().unwrap_or(0);
             result.push(LoginsPerDay {
                 date: day_names[idx].to_string(),
-                logins: row.logins as i64,
+                logins: row.logins,
+                failed_logins: row.failed_logins,
             });
         }
         Ok(result)
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::{
+    audit::{AuditAction, AuditEvent, AuditEventPage, AuditEventRow, AuditQuery},
+    session::ClientInfo,
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+pub struct NewAuditEvent<'a> {
+    pub action: AuditAction,
+    /// The user who performed the action, if any
+    pub actor_id: Option<Uuid>,
+    /// The user the action was performed on
+    pub target_id: Option<Uuid>,
+    pub client: &'a ClientInfo,
+    pub metadata: serde_json::Value,
+}
+
+/// Writes an audit event on any executor, so callers can commit it in the same
+/// transaction as the change it describes.
+pub async fn record_audit_event<'e, E>(executor: E, event: NewAuditEvent<'_>) -> Result<()>
+where
+    E: SqliteExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO audit_events (id, actor_id, actor_email, target_id, action, ip_address, metadata, created_at)
+        VALUES (?, ?, (SELECT email FROM users WHERE id = ?), ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(event.actor_id)
+    .bind(event.actor_id)
+    .bind(event.target_id)
+    .bind(event.action.as_str())
+    .bind(&event.client.ip_address)
+    .bind(event.metadata.to_string())
+    .bind(Utc::now())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub struct AuditService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AuditService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(&self, event: NewAuditEvent<'_>) -> Result<()> {
+        record_audit_event(self.pool, event).await
+    }
+
+    /// Newest events first, paged with an opaque cursor over `(created_at, id)`.
+    pub async fn list_events(&self, filter: &AuditQuery) -> Result<AuditEventPage> {
+        let limit = filter.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        let cursor = filter.cursor.as_deref().map(decode_cursor).transpose()?;
+
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM audit_events WHERE 1 = 1");
+        if let Some(actor_id) = filter.actor_id {
+            query.push(" AND actor_id = ").push_bind(actor_id);
+        }
+        if let Some(target_id) = filter.target_id {
+            query.push(" AND target_id = ").push_bind(target_id);
+        }
+        if let Some(action) = &filter.action {
+            query.push(" AND action = ").push_bind(action);
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some((created_at, id)) = cursor {
+            query
+                .push(" AND (created_at < ")
+                .push_bind(created_at)
+                .push(" OR (created_at = ")
+                .push_bind(created_at)
+                .push(" AND id < ")
+                .push_bind(id)
+                .push("))");
+        }
+        // One extra row tells us whether another page exists
+        query
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let mut rows = query
+            .build_query_as::<AuditEventRow>()
+            .fetch_all(self.pool)
+            .await?;
+
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| encode_cursor(row.created_at, row.id))
+        } else {
+            None
+        };
+
+        Ok(AuditEventPage {
+            events: rows.into_iter().map(AuditEvent::from).collect(),
+            next_cursor,
+        })
+    }
+}
+
+fn encode_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}|{}", created_at.to_rfc3339(), id))
+}
+
+fn decode_cursor(cursor: &str) -> Result<(DateTime<Utc>, Uuid)> {
+    let invalid = || anyhow!("Invalid cursor");
+
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (created_at, id) = decoded.split_once('|').ok_or_else(invalid)?;
+
+    let created_at = DateTime::parse_from_rfc3339(created_at)
+        .map_err(|_| invalid())?
+        .with_timezone(&Utc);
+    let id = id.parse().map_err(|_| invalid())?;
+
+    Ok((created_at, id))
+}
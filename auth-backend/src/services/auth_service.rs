@@ -1,29 +1,53 @@
 use anyhow::{anyhow, Result};
-use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::SqlitePool;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
+    config::{EmailVerificationPolicy, Settings},
     models::{
-        auth::{RegisterRequest, LoginRequest, AuthResponse},
+        audit::AuditAction,
+        auth::{RegisterRequest, LoginRequest, LoginResponse, AuthResponse, MfaChallenge, RefreshToken},
+        mfa::MfaVerifyRequest,
+        passkey::FinishPasskeyLoginRequest,
+        role::DEFAULT_ROLE,
+        session::ClientInfo,
         user::{User, UserRow},
     },
-    utils::{jwt::JwtKeys, password::hash_token},
+    services::{
+        audit_service::{record_audit_event, AuditService, NewAuditEvent},
+        hashing_pool::HashingPool,
+        lockout_service::{mfa_lockout_key, LockoutService},
+        login_event_service::{LoginEventService, LoginFailure, LoginMethod, LoginOutcome},
+        mfa_service::MfaService,
+        password_history::PasswordHistory, passkey_service::PasskeyService,
+        organization_service::OrganizationService, role_service::{assign_default_role, RoleService},
+        session_service::SessionService, token_revocation::TokenRevocationStore,
+    },
+    utils::{jwt::{JwtKeys, ACCESS_TOKEN_TTL_MINUTES}, password::hash_token},
 };
 
 pub struct AuthService<'a> {
     pool: &'a SqlitePool,
     jwt_keys: &'a Arc<JwtKeys>,
+    settings: &'a Settings,
+    revoked_tokens: &'a TokenRevocationStore,
+    hashing_pool: &'a HashingPool,
 }
 
 impl<'a> AuthService<'a> {
-    pub fn new(pool: &'a SqlitePool, jwt_keys: &'a Arc<JwtKeys>) -> Self {
-        Self { pool, jwt_keys }
+    pub fn new(
+        pool: &'a SqlitePool,
+        jwt_keys: &'a Arc<JwtKeys>,
+        settings: &'a Settings,
+        revoked_tokens: &'a TokenRevocationStore,
+        hashing_pool: &'a HashingPool,
+    ) -> Self {
+        Self { pool, jwt_keys, settings, revoked_tokens, hashing_pool }
     }
 
-    pub async fn register(&self, request: RegisterRequest) -> Result<AuthResponse> {
+    pub async fn register(&self, request: RegisterRequest, client: &ClientInfo) -> Result<AuthResponse> {
         // Check if user already exists
         let existing_user = sqlx::query_as::<_, UserRow>(
             "SELECT * FROM users WHERE email = ?",
@@ -37,158 +61,592 @@ impl<'a> AuthService<'a> {
         }
 
         // Hash password
-        let password_hash = hash(&request.password, DEFAULT_COST)?;
+        let password_hash = self.hashing_pool.hash(&request.password).await?;
 
         // Create user
         let user_id = Uuid::new_v4();
         let now = Utc::now();
 
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query(
             r#"
-            INSERT INTO users (id, email, password_hash, role, email_verified, terms_accepted, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO users (id, email, password_hash, role, email_verified, terms_accepted, created_at, updated_at, password_changed_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&user_id)
+        .bind(user_id)
         .bind(&request.email)
         .bind(&password_hash)
-        .bind("user")
+        .bind(DEFAULT_ROLE)
         .bind(false)
         .bind(request.agree_to_terms)
-        .bind(&now)
-        .bind(&now)
-        .execute(self.pool)
+        .bind(now)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
         .await?;
 
+        assign_default_role(&mut *tx, user_id).await?;
+
+        PasswordHistory::new(self.hashing_pool, self.settings.password_history_size)
+            .record(&mut tx, user_id, &password_hash)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::UserRegister,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({}),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
         // Fetch created user
         let user_row = sqlx::query_as::<_, UserRow>(
             "SELECT * FROM users WHERE id = ?",
         )
-        .bind(&user_id)
+        .bind(user_id)
         .fetch_one(self.pool)
         .await?;
 
         let user: User = user_row.into();
 
-        // Generate tokens
-        let (access_token, refresh_token) = self.generate_tokens(&user).await?;
+        // Registration signs the user in for this browser session only
+        let (access_token, refresh_token) = self.start_session(&user, false, client).await?;
 
         Ok(AuthResponse {
             user,
             token: access_token,
             refresh_token,
             expires_in: 600, // 10 minutes
+            password_change_required: None,
+            persistent: false,
         })
     }
 
-    pub async fn login(&self, request: LoginRequest) -> Result<AuthResponse> {
+    pub async fn login(&self, request: LoginRequest, client: &ClientInfo) -> Result<LoginResponse> {
+        let email = request.email.clone();
+        let result = self.password_login(request, client).await;
+
+        let (user_id, outcome) = match &result {
+            Ok(LoginResponse::Authenticated(auth)) => (Some(auth.user.id), LoginOutcome::Success),
+            Ok(LoginResponse::MfaRequired(_)) => (None, LoginOutcome::MfaRequired),
+            Err(e) => (None, LoginOutcome::Failure(LoginFailure::from_error(LoginMethod::Password, e))),
+        };
+        self.record_login_event(LoginMethod::Password, outcome, user_id, Some(&email), client).await;
+
+        result
+    }
+
+    async fn password_login(&self, request: LoginRequest, client: &ClientInfo) -> Result<LoginResponse> {
+        // Checked before the user lookup so unknown emails lock out the same way
+        let lockout_service = LockoutService::new(self.pool, self.settings);
+        lockout_service.ensure_not_locked(&request.email).await?;
+
         // Find user by email
         let user_row = sqlx::query_as::<_, UserRow>(
             "SELECT * FROM users WHERE email = ?",
         )
         .bind(&request.email)
         .fetch_optional(self.pool)
-        .await?
-        .ok_or_else(|| anyhow!("Invalid credentials"))?;
+        .await?;
 
         // Verify password
-        if !verify(&request.password, &user_row.password_hash)? {
-            return Err(anyhow!("Invalid credentials"));
+        let verified = match &user_row {
+            Some(row) => self.hashing_pool.verify(&request.password, &row.password_hash).await?,
+            None => false,
+        };
+        let user_row = match user_row {
+            Some(row) if verified => row,
+            _ => {
+                lockout_service.record_failure(&request.email).await?;
+                return Err(anyhow!("Invalid credentials"));
+            }
+        };
+
+        lockout_service.record_success(&request.email).await?;
+
+        if self.hashing_pool.needs_rehash(&user_row.password_hash) {
+            // The login itself does not depend on the upgrade succeeding
+            if let Err(e) = self.rehash_password(&user_row, &request.password).await {
+                tracing::error!("Password rehash error: {:?}", e);
+            }
         }
 
-        // Update last login
-        sqlx::query("UPDATE users SET last_login = ? WHERE id = ?")
-            .bind(&Utc::now())
-            .bind(&user_row.id)
-            .execute(self.pool)
-            .await?;
+        if self.settings.email_verification == EmailVerificationPolicy::RequiredForLogin
+            && !user_row.email_verified
+        {
+            return Err(anyhow!("Email not verified"));
+        }
 
-        let mut user: User = user_row.into();
-        user.last_login = Some(Utc::now());
+        let user: User = user_row.into();
 
-        // Generate tokens
-        let (access_token, refresh_token) = self.generate_tokens(&user).await?;
+        // Accounts with a second factor get a challenge instead of tokens
+        let mfa_service = MfaService::new(self.pool, self.settings);
+        if mfa_service.is_enabled(user.id).await? {
+            lockout_service.ensure_not_locked(&mfa_lockout_key(user.id)).await?;
 
-        Ok(AuthResponse {
-            user,
-            token: access_token,
-            refresh_token,
-            expires_in: 600, // 10 minutes
-        })
+            let ttl = Duration::minutes(self.settings.mfa_challenge_ttl_minutes);
+            let challenge_id = mfa_service.create_challenge(user.id, ttl).await?;
+            let mfa_token = self.jwt_keys.generate_mfa_token(&user, request.remember_me, ttl, challenge_id)?;
+
+            return Ok(LoginResponse::MfaRequired(MfaChallenge {
+                mfa_required: true,
+                mfa_token,
+                expires_in: ttl.num_seconds(),
+            }));
+        }
+
+        Ok(LoginResponse::Authenticated(self.complete_login(user, request.remember_me, client).await?))
     }
 
-    pub async fn logout(&self, user_id: Uuid) -> Result<()> {
-        // Delete all refresh tokens for the user
-        sqlx::query("DELETE FROM refresh_tokens WHERE user_id = ?")
-            .bind(&user_id)
-            .execute(self.pool)
+    /// Second login step for accounts with two-factor authentication enabled.
+    pub async fn verify_mfa(&self, request: MfaVerifyRequest, client: &ClientInfo) -> Result<AuthResponse> {
+        let claims = self
+            .jwt_keys
+            .verify_mfa_token(&request.mfa_token)
+            .ok()
+            .and_then(|claims| {
+                Some((claims.sub.parse::<Uuid>().ok()?, claims.jti.parse::<Uuid>().ok()?, claims.remember_me))
+            });
+
+        let Some((user_id, challenge_id, remember_me)) = claims else {
+            let outcome = LoginOutcome::Failure(LoginFailure::InvalidMfaToken);
+            self.record_login_event(LoginMethod::Mfa, outcome, None, None, client).await;
+            return Err(anyhow!("Invalid or expired MFA token"));
+        };
+
+        let result = self.complete_mfa(user_id, challenge_id, remember_me, &request.code, client).await;
+
+        let outcome = match &result {
+            Ok(_) => LoginOutcome::Success,
+            Err(e) => LoginOutcome::Failure(LoginFailure::from_error(LoginMethod::Mfa, e)),
+        };
+        self.record_login_event(LoginMethod::Mfa, outcome, Some(user_id), None, client).await;
+
+        result
+    }
+
+    async fn complete_mfa(
+        &self,
+        user_id: Uuid,
+        challenge_id: Uuid,
+        remember_me: bool,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse> {
+        let user_row = sqlx::query_as::<_, UserRow>(
+            "SELECT * FROM users WHERE id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Invalid or expired MFA token"))?;
+
+        let user: User = user_row.into();
+
+        let mfa_service = MfaService::new(self.pool, self.settings);
+        if !mfa_service.challenge_is_active(challenge_id, user.id).await? {
+            return Err(anyhow!("Invalid or expired MFA token"));
+        }
+
+        let lockout_service = LockoutService::new(self.pool, self.settings);
+        let lockout_key = mfa_lockout_key(user.id);
+        lockout_service.ensure_not_locked(&lockout_key).await?;
+
+        if !mfa_service.verify_second_factor(&user, code).await? {
+            mfa_service.record_challenge_failure(challenge_id).await?;
+            lockout_service.record_failure(&lockout_key).await?;
+            return Err(anyhow!("Invalid authentication code"));
+        }
+
+        // The token is single-use; a concurrent verification may have consumed it first
+        if !mfa_service.consume_challenge(challenge_id).await? {
+            return Err(anyhow!("Invalid or expired MFA token"));
+        }
+        lockout_service.record_success(&lockout_key).await?;
+
+        self.complete_login(user, remember_me, client).await
+    }
+
+    /// Signs out the session the access token belongs to; other devices stay signed in.
+    pub async fn logout(&self, user_id: Uuid, session_id: Uuid, client: &ClientInfo) -> Result<()> {
+        SessionService::new(self.pool, self.revoked_tokens)
+            .revoke_session(user_id, session_id)
+            .await?;
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::UserLogout,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "session_id": session_id }),
+            })
             .await?;
 
         Ok(())
     }
 
-    pub async fn refresh_token(&self, refresh_token: String) -> Result<AuthResponse> {
+    pub async fn refresh_token(&self, refresh_token: String, client: &ClientInfo) -> Result<AuthResponse> {
         // Verify refresh token
         let _claims = self.jwt_keys.verify_refresh_token(&refresh_token)?;
         
         // Check if refresh token exists in database
         let token_hash = hash_token(&refresh_token);
         let now = Utc::now();
-        let stored_token = sqlx::query!(
-            "SELECT user_id FROM refresh_tokens WHERE token_hash = ? AND expires_at > ?",
-            token_hash,
-            now
+        let stored_token = sqlx::query_as::<_, RefreshToken>(
+            "SELECT * FROM refresh_tokens WHERE token_hash = ?",
         )
+        .bind(&token_hash)
         .fetch_optional(self.pool)
         .await?
         .ok_or_else(|| anyhow!("Invalid refresh token"))?;
 
+        if stored_token.used_at.is_some() {
+            self.revoke_token_family(&stored_token, client).await?;
+            return Err(anyhow!("Refresh token reuse detected"));
+        }
+
+        if stored_token.expires_at <= now {
+            return Err(anyhow!("Invalid refresh token"));
+        }
+
+        // Mark the old token used; the condition makes concurrent rotations of one token a replay too
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL",
+        )
+        .bind(now)
+        .bind(stored_token.id)
+        .execute(self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            self.revoke_token_family(&stored_token, client).await?;
+            return Err(anyhow!("Refresh token reuse detected"));
+        }
+
         // Get user
         let user_row = sqlx::query_as::<_, UserRow>(
             "SELECT * FROM users WHERE id = ?",
         )
-        .bind(&stored_token.user_id)
+        .bind(stored_token.user_id)
         .fetch_one(self.pool)
         .await?;
 
         let user: User = user_row.into();
 
-        // Delete old refresh token
-        sqlx::query("DELETE FROM refresh_tokens WHERE token_hash = ?")
-            .bind(&token_hash)
+        // Used tokens are only kept for reuse detection while they could still be presented
+        sqlx::query("DELETE FROM refresh_tokens WHERE user_id = ? AND expires_at <= ?")
+            .bind(user.id)
+            .bind(now)
             .execute(self.pool)
             .await?;
 
-        // Generate new tokens
-        let (access_token, new_refresh_token) = self.generate_tokens(&user).await?;
+        SessionService::new(self.pool, self.revoked_tokens)
+            .touch_session(stored_token.family_id, client)
+            .await?;
+
+        // Generate new tokens in the same family, keeping the lifetime policy chosen at login
+        let (access_token, new_refresh_token) = self
+            .generate_tokens(&user, stored_token.family_id, stored_token.persistent)
+            .await?;
+
+        let password_change_required = user.password_change_reason(self.settings.password_max_age_days);
 
         Ok(AuthResponse {
             user,
             token: access_token,
             refresh_token: new_refresh_token,
             expires_in: 600, // 10 minutes
+            password_change_required,
+            persistent: stored_token.persistent,
+        })
+    }
+
+    /// Makes the session act in another of the user's organizations. The
+    /// session's current tokens are retired and a new pair carrying the
+    /// organization is issued, so the old access token stops working at once.
+    pub async fn switch_organization(
+        &self,
+        user: User,
+        session_id: Uuid,
+        organization_id: Uuid,
+    ) -> Result<AuthResponse> {
+        let organization_service = OrganizationService::new(self.pool);
+        if organization_service.membership(organization_id, user.id).await?.is_none() {
+            return Err(anyhow!("Organization not found"));
+        }
+
+        let issued_after = Utc::now() - Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+        let current: Vec<(bool, Option<Uuid>, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT persistent, access_jti, created_at FROM refresh_tokens
+            WHERE family_id = ? AND used_at IS NULL AND expires_at > ?
+            "#,
+        )
+        .bind(session_id)
+        .bind(Utc::now())
+        .fetch_all(self.pool)
+        .await?;
+
+        let persistent = current
+            .first()
+            .map(|(persistent, _, _)| *persistent)
+            .ok_or_else(|| anyhow!("Session has expired"))?;
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE family_id = ? AND used_at IS NULL")
+            .bind(session_id)
+            .execute(self.pool)
+            .await?;
+
+        let retired: Vec<(Uuid, DateTime<Utc>)> = current
+            .into_iter()
+            .filter(|(_, _, issued_at)| *issued_at > issued_after)
+            .filter_map(|(_, jti, issued_at)| {
+                jti.map(|jti| (jti, issued_at + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES)))
+            })
+            .collect();
+        self.revoked_tokens.revoke(&retired).await?;
+
+        organization_service.set_active_organization(session_id, organization_id).await?;
+
+        let (access_token, refresh_token) = self.generate_tokens(&user, session_id, persistent).await?;
+        let password_change_required = user.password_change_reason(self.settings.password_max_age_days);
+
+        Ok(AuthResponse {
+            user,
+            token: access_token,
+            refresh_token,
+            expires_in: 600, // 10 minutes
+            password_change_required,
+            persistent,
+        })
+    }
+
+    /// A rotated token was presented again, so either the client or an attacker
+    /// holds a stolen copy. Revoke every token descended from the same login.
+    async fn revoke_token_family(&self, token: &RefreshToken, client: &ClientInfo) -> Result<()> {
+        tracing::warn!(
+            target: "security",
+            user_id = %token.user_id,
+            family_id = %token.family_id,
+            "Refresh token reuse detected; revoking token family"
+        );
+
+        SessionService::new(self.pool, self.revoked_tokens)
+            .revoke_session(token.user_id, token.family_id)
+            .await?;
+
+        // No actor: the presenter of the token may or may not be the user
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::RefreshTokenReuse,
+                actor_id: None,
+                target_id: Some(token.user_id),
+                client,
+                metadata: serde_json::json!({ "session_id": token.family_id }),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Passwordless login with a passkey. The passkey counts as both factors,
+    /// so no TOTP challenge follows.
+    pub async fn login_with_passkey(
+        &self,
+        request: FinishPasskeyLoginRequest,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse> {
+        let passkey_service = PasskeyService::new(self.pool, self.settings);
+        let user_id = match passkey_service.finish_authentication(request.credential).await {
+            Ok(user_id) => user_id,
+            Err(e) => {
+                let outcome = LoginOutcome::Failure(LoginFailure::from_error(LoginMethod::Passkey, &e));
+                self.record_login_event(LoginMethod::Passkey, outcome, None, None, client).await;
+                return Err(e);
+            }
+        };
+
+        let result = self.complete_passkey_login(user_id, request.remember_me, client).await;
+
+        let outcome = match &result {
+            Ok(_) => LoginOutcome::Success,
+            Err(e) => LoginOutcome::Failure(LoginFailure::from_error(LoginMethod::Passkey, e)),
+        };
+        self.record_login_event(LoginMethod::Passkey, outcome, Some(user_id), None, client).await;
+
+        result
+    }
+
+    async fn complete_passkey_login(
+        &self,
+        user_id: Uuid,
+        remember_me: bool,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse> {
+        let user_row = sqlx::query_as::<_, UserRow>(
+            "SELECT * FROM users WHERE id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(self.pool)
+        .await?;
+
+        if self.settings.email_verification == EmailVerificationPolicy::RequiredForLogin
+            && !user_row.email_verified
+        {
+            return Err(anyhow!("Email not verified"));
+        }
+
+        self.complete_login(user_row.into(), remember_me, client).await
+    }
+
+    /// Upgrades a hash made with an older algorithm or parameters, now that the
+    /// plaintext is known to be correct.
+    async fn rehash_password(&self, user_row: &UserRow, password: &str) -> Result<()> {
+        let new_hash = self.hashing_pool.hash(password).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Conditional on the old hash so a concurrent password change wins
+        let result = sqlx::query("UPDATE users SET password_hash = ? WHERE id = ? AND password_hash = ?")
+            .bind(&new_hash)
+            .bind(user_row.id)
+            .bind(&user_row.password_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 1 {
+            PasswordHistory::new(self.hashing_pool, self.settings.password_history_size)
+                .replace_hash(&mut tx, user_row.id, &user_row.password_hash, &new_hash)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Failing to write the event must not fail the login itself.
+    async fn record_login_event(
+        &self,
+        method: LoginMethod,
+        outcome: LoginOutcome,
+        user_id: Option<Uuid>,
+        email: Option<&str>,
+        client: &ClientInfo,
+    ) {
+        if let Err(e) = LoginEventService::new(self.pool)
+            .record(method, outcome, user_id, email, client)
+            .await
+        {
+            tracing::error!("Failed to record login event: {:?}", e);
+        }
+    }
+
+    async fn complete_login(
+        &self,
+        mut user: User,
+        remember_me: bool,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse> {
+        // Update last login
+        let now = Utc::now();
+        sqlx::query("UPDATE users SET last_login = ? WHERE id = ?")
+            .bind(now)
+            .bind(user.id)
+            .execute(self.pool)
+            .await?;
+
+        user.last_login = Some(now);
+
+        // Generate tokens
+        let (access_token, refresh_token) = self.start_session(&user, remember_me, client).await?;
+        let password_change_required = user.password_change_reason(self.settings.password_max_age_days);
+
+        Ok(AuthResponse {
+            user,
+            token: access_token,
+            refresh_token,
+            expires_in: 600, // 10 minutes
+            password_change_required,
+            persistent: remember_me,
         })
     }
 
-    async fn generate_tokens(&self, user: &User) -> Result<(String, String)> {
-        let access_token = self.jwt_keys.generate_access_token(user)?;
-        let refresh_token = self.jwt_keys.generate_refresh_token(user)?;
+    /// Persistent ("remember me") refresh tokens live for days; session-scoped ones for hours.
+    fn refresh_token_ttl(&self, persistent: bool) -> Duration {
+        if persistent {
+            Duration::days(self.settings.refresh_token_persistent_ttl_days)
+        } else {
+            Duration::hours(self.settings.refresh_token_session_ttl_hours)
+        }
+    }
+
+    async fn start_session(
+        &self,
+        user: &User,
+        persistent: bool,
+        client: &ClientInfo,
+    ) -> Result<(String, String)> {
+        let session_id = SessionService::new(self.pool, self.revoked_tokens)
+            .create_session(user.id, client)
+            .await?;
+
+        self.generate_tokens(user, session_id, persistent).await
+    }
+
+    /// Issues an access/refresh token pair for a session. The session id is also
+    /// the family id shared by every refresh token rotated from the same login.
+    async fn generate_tokens(
+        &self,
+        user: &User,
+        family_id: Uuid,
+        persistent: bool,
+    ) -> Result<(String, String)> {
+        let ttl = self.refresh_token_ttl(persistent);
+        let token_id = Uuid::new_v4();
+        let access_jti = Uuid::new_v4();
+        // Permissions are embedded at issue time, so role changes apply from the next refresh
+        let user_roles = RoleService::new(self.pool).user_roles(user.id).await?;
+        let membership = OrganizationService::new(self.pool)
+            .session_membership(family_id, user.id)
+            .await?;
+        let access_token = self.jwt_keys.generate_access_token(
+            user,
+            &user_roles,
+            membership.as_ref(),
+            family_id,
+            access_jti,
+        )?;
+        let refresh_token = self.jwt_keys.generate_refresh_token(user, token_id, ttl)?;
 
         // Store refresh token in database
         let token_hash = hash_token(&refresh_token);
-        let expires_at = Utc::now() + Duration::days(7);
+        let now = Utc::now();
+        let expires_at = now + ttl;
 
         sqlx::query(
             r#"
-            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at, persistent, family_id, access_jti)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&Uuid::new_v4())
-        .bind(&user.id)
+        .bind(token_id)
+        .bind(user.id)
         .bind(&token_hash)
-        .bind(&expires_at)
-        .bind(&Utc::now())
+        .bind(expires_at)
+        .bind(now)
+        .bind(persistent)
+        .bind(family_id)
+        .bind(access_jti)
         .execute(self.pool)
         .await?;
 
@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Context, Result};
+use memmap2::Mmap;
+use sha1::{Digest, Sha1};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fs::File,
+    path::{Path, PathBuf},
+};
+use validator::ValidationError;
+
+use crate::config::Settings;
+
+const HASH_HEX_LENGTH: usize = 40;
+const PREFIX_HEX_LENGTH: usize = 5;
+/// Lines checked at startup in a single-file list, besides the first and last.
+const SAMPLED_LINES: usize = 256;
+/// Range files checked at startup, spread evenly over the prefixes.
+const SAMPLED_RANGES: u32 = 16;
+
+/// Offline lookup of passwords that appear in known breaches.
+///
+/// Accepts either layout of the Have I Been Pwned download:
+///
+/// - the range format: a directory with one `<PREFIX>.txt` file per
+///   5-character hash prefix, each holding `<35-character suffix>:<count>`
+///   lines sorted by suffix
+/// - a single file of full `<SHA-1>:<count>` lines sorted by hash
+///
+/// Hashes are uppercase hex. Lookups memory-map the file and binary search it,
+/// so even the full multi-gigabyte list costs a few page reads per lookup and
+/// no network calls.
+pub struct BreachedPasswords {
+    corpus: Option<Corpus>,
+    min_occurrences: u64,
+}
+
+enum Corpus {
+    Single(Mmap),
+    Ranges(PathBuf),
+}
+
+impl BreachedPasswords {
+    /// Opens `BREACHED_PASSWORDS_PATH`. Without it every password passes the check.
+    pub fn load(settings: &Settings) -> Result<Self> {
+        let Some(path) = &settings.breached_passwords_path else {
+            return Ok(Self::disabled());
+        };
+
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Failed to open breached password list {}", path))?;
+
+        // A malformed or out of order line would make the binary search miss
+        // entries. Scanning the whole download takes minutes, so startup checks
+        // a sample, which is enough to catch the wrong layout, a truncated file
+        // or an unsorted one
+        let corpus = if metadata.is_dir() {
+            validate_ranges(Path::new(path))
+                .with_context(|| format!("Invalid breached password list {}", path))?;
+            Corpus::Ranges(PathBuf::from(path))
+        } else {
+            let corpus = map(Path::new(path))?;
+            validate_sample(&corpus)
+                .with_context(|| format!("Invalid breached password list {}", path))?;
+            Corpus::Single(corpus)
+        };
+
+        tracing::info!(
+            path = %path,
+            layout = if metadata.is_dir() { "ranges" } else { "single file" },
+            min_occurrences = settings.breached_passwords_min_count,
+            "Loaded breached password list"
+        );
+
+        Ok(Self {
+            corpus: Some(corpus),
+            min_occurrences: settings.breached_passwords_min_count,
+        })
+    }
+
+    pub fn disabled() -> Self {
+        Self { corpus: None, min_occurrences: 1 }
+    }
+
+    /// How often the password was seen in breaches; 0 if it is not in the list.
+    pub fn occurrences(&self, password: &str) -> u64 {
+        let Some(corpus) = &self.corpus else {
+            return 0;
+        };
+
+        let target = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        match corpus {
+            Corpus::Single(corpus) => find(corpus, target.as_bytes()).unwrap_or(0),
+            Corpus::Ranges(directory) => {
+                let (prefix, suffix) = target.split_at(PREFIX_HEX_LENGTH);
+                match map(&range_path(directory, prefix)) {
+                    Ok(range) => find(&range, suffix.as_bytes()).unwrap_or(0),
+                    Err(e) => {
+                        tracing::error!("Breached password lookup failed: {:#}", e);
+                        0
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_breached(&self, password: &str) -> bool {
+        self.occurrences(password) >= self.min_occurrences.max(1)
+    }
+
+    /// The validation error reported alongside password policy violations.
+    pub fn check(&self, password: &str) -> Option<ValidationError> {
+        self.is_breached(password).then(|| {
+            ValidationError::new("password_breached").with_message(Cow::Borrowed(
+                "This password has appeared in a data breach. Please choose a different one",
+            ))
+        })
+    }
+}
+
+fn map(path: &Path) -> Result<Mmap> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open breached password list {}", path.display()))?;
+    // SAFETY: the mapping is read-only. The files must not be truncated or
+    // rewritten while the server runs; replace them and restart instead.
+    unsafe { Mmap::map(&file) }
+        .with_context(|| format!("Failed to map breached password list {}", path.display()))
+}
+
+fn range_path(directory: &Path, prefix: &str) -> PathBuf {
+    directory.join(format!("{}.txt", prefix))
+}
+
+/// Binary search over variable-length lines whose hash has `target`'s length.
+/// `low` always sits at a line start.
+fn find(corpus: &[u8], target: &[u8]) -> Option<u64> {
+    let (mut low, mut high) = (0, corpus.len());
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let start = line_start(&corpus[..middle], low);
+        let end = corpus[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(corpus.len(), |offset| start + offset);
+
+        let (hash, count) = parse_line(&corpus[start..end], target.len())?;
+        match hash.cmp(target) {
+            Ordering::Equal => return Some(count),
+            Ordering::Less => low = end + 1,
+            Ordering::Greater => high = start,
+        }
+    }
+
+    None
+}
+
+/// Start of the line containing the end of `corpus`, not searching before `low`.
+fn line_start(corpus: &[u8], low: usize) -> usize {
+    corpus[low..]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(low, |offset| low + offset + 1)
+}
+
+/// Checks the first and last lines and evenly spaced lines in between, each
+/// together with the line after it.
+fn validate_sample(corpus: &[u8]) -> Result<()> {
+    let corpus = corpus.strip_suffix(b"\n").unwrap_or(corpus);
+    if corpus.is_empty() {
+        return Err(anyhow!("The file is empty"));
+    }
+
+    let mut previous: Option<(usize, &[u8])> = None;
+    for sample in 0..=SAMPLED_LINES {
+        let offset = (corpus.len() - 1) * sample / SAMPLED_LINES;
+        let mut start = line_start(&corpus[..offset], 0);
+
+        for line in corpus[start..].split(|&b| b == b'\n').take(2) {
+            // Small files put several samples on one line
+            if previous.is_none_or(|(checked, _)| start > checked) {
+                let (hash, _) = parse_line(line, HASH_HEX_LENGTH).ok_or_else(|| {
+                    anyhow!("The line at byte {} is not in <hash>:<count> format", start)
+                })?;
+
+                if previous.is_some_and(|(_, previous)| previous >= hash) {
+                    return Err(anyhow!("The line at byte {} is not in ascending hash order", start));
+                }
+                previous = Some((start, hash));
+            }
+            start += line.len() + 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a spread of range files, each in full since they are small.
+fn validate_ranges(directory: &Path) -> Result<()> {
+    let last_prefix = (1u32 << (4 * PREFIX_HEX_LENGTH)) - 1;
+
+    for sample in 0..=SAMPLED_RANGES {
+        let prefix = format!("{:05X}", last_prefix * sample / SAMPLED_RANGES);
+        let path = range_path(directory, &prefix);
+        if !path.is_file() {
+            return Err(anyhow!(
+                "{} is missing; the range format has one <PREFIX>.txt file for every prefix from 00000 to FFFFF",
+                path.display()
+            ));
+        }
+
+        let range = map(&path)?;
+        let range = range.strip_suffix(b"\n").unwrap_or(&range);
+        let mut previous: Option<&[u8]> = None;
+        for (index, line) in range.split(|&b| b == b'\n').enumerate() {
+            let (suffix, _) = parse_line(line, HASH_HEX_LENGTH - PREFIX_HEX_LENGTH).ok_or_else(|| {
+                anyhow!("Line {} of {}.txt is not in <suffix>:<count> format", index + 1, prefix)
+            })?;
+
+            if previous.is_some_and(|previous| previous >= suffix) {
+                return Err(anyhow!("Line {} of {}.txt is not in ascending hash order", index + 1, prefix));
+            }
+            previous = Some(suffix);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_line(line: &[u8], hash_length: usize) -> Option<(&[u8], u64)> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let (hash, count) = line.split_at_checked(hash_length)?;
+    let count = count.strip_prefix(b":")?;
+
+    if !hash.iter().all(|b| b.is_ascii_digit() || (b'A'..=b'F').contains(b)) {
+        return None;
+    }
+
+    let count = std::str::from_utf8(count).ok()?.trim().parse().ok()?;
+    Some((hash, count))
+}
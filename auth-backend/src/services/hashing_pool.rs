@@ -0,0 +1,258 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fmt::{self, Write as _},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+
+use crate::{config::Settings, utils::password::PasswordHasher};
+
+/// Histogram bucket upper bounds in seconds. Argon2id with the default
+/// parameters takes a few tens of milliseconds per hash.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Used for `Retry-After` before any job has completed.
+const ASSUMED_JOB_DURATION: Duration = Duration::from_millis(100);
+
+/// Returned when the hashing queue is full. Handlers downcast to it to answer
+/// 503 with `Retry-After`.
+#[derive(Debug)]
+pub struct HashingPoolSaturated {
+    pub retry_after_seconds: u64,
+}
+
+impl fmt::Display for HashingPoolSaturated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The server is busy. Try again shortly")
+    }
+}
+
+impl std::error::Error for HashingPoolSaturated {}
+
+struct Job {
+    queued_at: Instant,
+    task: Box<dyn FnOnce(&PasswordHasher) + Send>,
+}
+
+/// Runs password hashing and verification on dedicated threads.
+///
+/// Hashes are deliberately slow, so running them on Tokio workers lets a burst
+/// of logins stall every other request. Jobs wait in a queue of at most
+/// `HASHING_QUEUE_LIMIT` entries; beyond that they fail fast with
+/// `HashingPoolSaturated` instead of piling up.
+pub struct HashingPool {
+    hasher: Arc<PasswordHasher>,
+    sender: SyncSender<Job>,
+    workers: usize,
+    queue_limit: usize,
+    metrics: Arc<HashingMetrics>,
+}
+
+impl HashingPool {
+    pub fn start(settings: &Settings) -> Result<Self> {
+        if settings.hashing_workers == 0 {
+            return Err(anyhow!("HASHING_WORKERS must be at least 1"));
+        }
+
+        let hasher = Arc::new(PasswordHasher::from_settings(settings)?);
+        let metrics = Arc::new(HashingMetrics::default());
+        let (sender, receiver) = mpsc::sync_channel::<Job>(settings.hashing_queue_limit);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for index in 0..settings.hashing_workers {
+            let receiver = receiver.clone();
+            let hasher = hasher.clone();
+            let metrics = metrics.clone();
+
+            thread::Builder::new()
+                .name(format!("password-hash-{}", index))
+                .spawn(move || loop {
+                    // The lock is only held while waiting for the next job
+                    let job = match receiver.lock().map(|receiver| receiver.recv()) {
+                        Ok(Ok(job)) => job,
+                        _ => break,
+                    };
+
+                    metrics.started(job.queued_at.elapsed());
+                    let started_at = Instant::now();
+                    if panic::catch_unwind(AssertUnwindSafe(|| (job.task)(&hasher))).is_err() {
+                        tracing::error!("Password hashing job panicked");
+                    }
+                    metrics.finished(started_at.elapsed());
+                })?;
+        }
+
+        tracing::info!(
+            workers = settings.hashing_workers,
+            queue_limit = settings.hashing_queue_limit,
+            "Started password hashing pool"
+        );
+
+        Ok(Self {
+            hasher,
+            sender,
+            workers: settings.hashing_workers,
+            queue_limit: settings.hashing_queue_limit,
+            metrics,
+        })
+    }
+
+    pub async fn hash(&self, password: &str) -> Result<String> {
+        let password = password.to_owned();
+        self.run(move |hasher| hasher.hash(&password)).await
+    }
+
+    pub async fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        let (password, hash) = (password.to_owned(), hash.to_owned());
+        self.run(move |hasher| hasher.verify(&password, &hash)).await
+    }
+
+    /// Only parses the hash, so it runs inline.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        self.hasher.needs_rehash(hash)
+    }
+
+    async fn run<T, F>(&self, task: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PasswordHasher) -> Result<T> + Send + 'static,
+    {
+        let (reply, outcome) = oneshot::channel();
+        let job = Job {
+            queued_at: Instant::now(),
+            task: Box::new(move |hasher| {
+                // The caller may be gone if the client disconnected
+                let _ = reply.send(task(hasher));
+            }),
+        };
+
+        self.metrics.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(error) = self.sender.try_send(job) {
+            self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(match error {
+                TrySendError::Full(_) => {
+                    self.metrics.rejected_total.fetch_add(1, Ordering::Relaxed);
+                    HashingPoolSaturated { retry_after_seconds: self.retry_after_seconds() }.into()
+                }
+                TrySendError::Disconnected(_) => anyhow!("Password hashing pool has stopped"),
+            });
+        }
+
+        outcome
+            .await
+            .map_err(|_| anyhow!("Password hashing job did not complete"))?
+    }
+
+    /// Roughly how long until the current backlog has drained.
+    fn retry_after_seconds(&self) -> u64 {
+        let average = self.metrics.duration.average().unwrap_or(ASSUMED_JOB_DURATION);
+        let backlog = self.metrics.queue_depth.load(Ordering::Relaxed).max(self.queue_limit) + self.workers;
+        let seconds = average.as_secs_f64() * backlog as f64 / self.workers as f64;
+
+        (seconds.ceil() as u64).max(1)
+    }
+
+    /// Prometheus text exposition of the pool's gauges, counters and histograms.
+    pub fn render_metrics(&self) -> String {
+        let metrics = &self.metrics;
+        let mut out = String::new();
+
+        let gauges = [
+            ("password_hashing_workers", "Threads dedicated to password hashing", self.workers),
+            ("password_hashing_busy_workers", "Workers currently hashing", metrics.busy_workers.load(Ordering::Relaxed)),
+            ("password_hashing_queue_depth", "Jobs waiting for a worker", metrics.queue_depth.load(Ordering::Relaxed)),
+            ("password_hashing_queue_limit", "Jobs allowed to wait before requests are rejected", self.queue_limit),
+        ];
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}");
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP password_hashing_rejected_total Jobs rejected because the queue was full\n\
+             # TYPE password_hashing_rejected_total counter\n\
+             password_hashing_rejected_total {}",
+            metrics.rejected_total.load(Ordering::Relaxed)
+        );
+
+        metrics.queue_wait.render(
+            &mut out,
+            "password_hashing_queue_wait_seconds",
+            "Time jobs spent waiting for a worker",
+        );
+        metrics.duration.render(
+            &mut out,
+            "password_hashing_duration_seconds",
+            "Time spent hashing or verifying a password",
+        );
+
+        out
+    }
+}
+
+#[derive(Default)]
+struct HashingMetrics {
+    queue_depth: AtomicUsize,
+    busy_workers: AtomicUsize,
+    rejected_total: AtomicU64,
+    queue_wait: Histogram,
+    duration: Histogram,
+}
+
+impl HashingMetrics {
+    fn started(&self, waited: Duration) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        self.busy_workers.fetch_add(1, Ordering::Relaxed);
+        self.queue_wait.observe(waited);
+    }
+
+    fn finished(&self, took: Duration) {
+        self.busy_workers.fetch_sub(1, Ordering::Relaxed);
+        self.duration.observe(took);
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Per-bucket counts; rendering makes them cumulative
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, value: Duration) {
+        let seconds = value.as_secs_f64();
+        if let Some(index) = LATENCY_BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(value.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn average(&self) -> Option<Duration> {
+        let count = self.count.load(Ordering::Relaxed);
+        (count > 0).then(|| Duration::from_micros(self.sum_micros.load(Ordering::Relaxed) / count))
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} histogram");
+
+        let mut cumulative = 0;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}\n{name}_sum {sum}\n{name}_count {count}");
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::config::Settings;
+
+/// Returned by `AuthService::login` while an email is locked out. Handlers
+/// downcast to it to report the distinct status and `Retry-After`.
+#[derive(Debug)]
+pub struct AccountLocked {
+    pub locked_until: DateTime<Utc>,
+}
+
+impl AccountLocked {
+    pub fn retry_after_seconds(&self) -> i64 {
+        (self.locked_until - Utc::now()).num_seconds().max(1)
+    }
+}
+
+impl fmt::Display for AccountLocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Too many failed login attempts. Try again later")
+    }
+}
+
+impl std::error::Error for AccountLocked {}
+
+/// Counts failed password logins per email and locks the email out once the
+/// threshold is reached. Each consecutive lockout doubles in length.
+pub struct LockoutService<'a> {
+    pool: &'a SqlitePool,
+    settings: &'a Settings,
+}
+
+impl<'a> LockoutService<'a> {
+    pub fn new(pool: &'a SqlitePool, settings: &'a Settings) -> Self {
+        Self { pool, settings }
+    }
+
+    /// Fails with `AccountLocked` while the email is locked out.
+    pub async fn ensure_not_locked(&self, email: &str) -> Result<()> {
+        let locked_until: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT locked_until FROM login_lockouts WHERE email = ?")
+                .bind(normalize_email(email))
+                .fetch_optional(self.pool)
+                .await?
+                .flatten();
+
+        if let Some(locked_until) = locked_until {
+            if locked_until > Utc::now() {
+                return Err(AccountLocked { locked_until }.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a failed attempt, locking the email out when the threshold is reached.
+    ///
+    /// The counter is incremented in the database rather than read and written
+    /// back, so concurrent failures are all counted. The upsert takes the write
+    /// lock, so the lockout decision in the same transaction sees its result.
+    pub async fn record_failure(&self, email: &str) -> Result<()> {
+        let now = Utc::now();
+        let max_lockout = Duration::minutes(self.settings.lockout_max_minutes);
+        let email = normalize_email(email);
+
+        let mut tx = self.pool.begin().await?;
+
+        // Counters are forgotten after a quiet period as long as the longest lockout
+        let (failed_attempts, lockout_count): (i64, i64) = sqlx::query_as(
+            r#"
+            INSERT INTO login_lockouts (email, failed_attempts, lockout_count, last_failed_at)
+            VALUES (?, 1, 0, ?)
+            ON CONFLICT(email) DO UPDATE SET
+                failed_attempts = CASE WHEN last_failed_at > ? THEN failed_attempts + 1 ELSE 1 END,
+                lockout_count = CASE WHEN last_failed_at > ? THEN lockout_count ELSE 0 END,
+                last_failed_at = excluded.last_failed_at
+            RETURNING failed_attempts, lockout_count
+            "#,
+        )
+        .bind(&email)
+        .bind(now)
+        .bind(now - max_lockout)
+        .bind(now - max_lockout)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if failed_attempts >= self.settings.lockout_threshold {
+            let backoff = 2i64.saturating_pow(lockout_count.clamp(0, 30) as u32);
+            let duration = Duration::minutes(self.settings.lockout_base_minutes.saturating_mul(backoff))
+                .min(max_lockout);
+
+            sqlx::query(
+                r#"
+                UPDATE login_lockouts
+                SET failed_attempts = 0, lockout_count = lockout_count + 1, locked_until = ?
+                WHERE email = ?
+                "#,
+            )
+            .bind(now + duration)
+            .bind(&email)
+            .execute(&mut *tx)
+            .await?;
+
+            tracing::warn!(
+                target: "security",
+                email = %email,
+                lockout_count = lockout_count + 1,
+                "Login locked out after repeated failures"
+            );
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// A successful login clears the failure history.
+    pub async fn record_success(&self, email: &str) -> Result<()> {
+        sqlx::query("DELETE FROM login_lockouts WHERE email = ?")
+            .bind(normalize_email(email))
+            .execute(self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Clears the lockout for a user's email. Returns `false` if the user does not exist.
+    pub async fn unlock_user(&self, user_id: Uuid) -> Result<bool> {
+        let email: Option<String> = sqlx::query_scalar("SELECT email FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(self.pool)
+            .await?;
+
+        let Some(email) = email else {
+            return Ok(false);
+        };
+
+        self.record_success(&email).await?;
+        self.record_success(&mfa_lockout_key(user_id)).await?;
+
+        Ok(true)
+    }
+}
+
+/// Wrong second-factor codes are counted under their own key, so a correct
+/// password, which clears the email's counter, does not reset them.
+pub fn mfa_lockout_key(user_id: Uuid) -> String {
+    format!("mfa:{}", user_id)
+}
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
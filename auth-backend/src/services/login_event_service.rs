@@ -0,0 +1,142 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{models::session::ClientInfo, services::lockout_service::AccountLocked};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginMethod {
+    Password,
+    Mfa,
+    Passkey,
+}
+
+impl LoginMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Password => "password",
+            Self::Mfa => "mfa",
+            Self::Passkey => "passkey",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginFailure {
+    InvalidCredentials,
+    AccountLocked,
+    EmailNotVerified,
+    InvalidMfaToken,
+    InvalidMfaCode,
+    PasskeyRejected,
+    Error,
+}
+
+impl LoginFailure {
+    /// Classifies an error returned by one of the `AuthService` login flows.
+    pub fn from_error(method: LoginMethod, error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<AccountLocked>().is_some() {
+            return Self::AccountLocked;
+        }
+        if error.downcast_ref::<sqlx::Error>().is_some() {
+            return Self::Error;
+        }
+
+        let message = error.to_string();
+        if message.contains("Invalid credentials") {
+            Self::InvalidCredentials
+        } else if message.contains("Email not verified") {
+            Self::EmailNotVerified
+        } else if message.contains("Invalid or expired MFA token") {
+            Self::InvalidMfaToken
+        } else if message.contains("Invalid authentication code") {
+            Self::InvalidMfaCode
+        } else if method == LoginMethod::Passkey {
+            // Every other passkey error is a failed ceremony
+            Self::PasskeyRejected
+        } else {
+            Self::Error
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidCredentials => "invalid_credentials",
+            Self::AccountLocked => "account_locked",
+            Self::EmailNotVerified => "email_not_verified",
+            Self::InvalidMfaToken => "invalid_mfa_token",
+            Self::InvalidMfaCode => "invalid_mfa_code",
+            Self::PasskeyRejected => "passkey_rejected",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    Success,
+    /// The password was accepted and a second factor was requested
+    MfaRequired,
+    Failure(LoginFailure),
+}
+
+impl LoginOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::MfaRequired => "mfa_required",
+            Self::Failure(_) => "failure",
+        }
+    }
+
+    pub fn failure_reason(&self) -> Option<&'static str> {
+        match self {
+            Self::Failure(reason) => Some(reason.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Append-only log of login attempts backing the admin dashboard and analytics.
+pub struct LoginEventService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LoginEventService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Records one attempt. When only the email is known, the user is looked up
+    /// by it so failed password logins against real accounts stay attributable.
+    pub async fn record(
+        &self,
+        method: LoginMethod,
+        outcome: LoginOutcome,
+        user_id: Option<Uuid>,
+        email: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO login_events (id, user_id, email, method, outcome, failure_reason, ip_address, user_agent, created_at)
+            VALUES (?, COALESCE(?, (SELECT id FROM users WHERE email = ?)), ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(email)
+        .bind(email)
+        .bind(method.as_str())
+        .bind(outcome.as_str())
+        .bind(outcome.failure_reason())
+        .bind(&client.ip_address)
+        .bind(&client.user_agent)
+        .bind(Utc::now())
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::{path::PathBuf, sync::Arc};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Settings;
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outbound email delivery. Implementations must be cheap to share across requests.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: EmailMessage) -> Result<()>;
+}
+
+/// Development mailer that logs every message and optionally appends it to an outbox file.
+pub struct LogMailer {
+    outbox: Option<PathBuf>,
+}
+
+impl LogMailer {
+    pub fn new(outbox: Option<PathBuf>) -> Self {
+        Self { outbox }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        tracing::info!(to = %message.to, subject = %message.subject, "Outgoing email:\n{}", message.body);
+
+        if let Some(path) = &self.outbox {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            let entry = format!(
+                "To: {}\nSubject: {}\n\n{}\n\n---\n",
+                message.to, message.subject, message.body
+            );
+            file.write_all(entry.as_bytes()).await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: Option<String>,
+        password: Option<String>,
+        from: &str,
+    ) -> Result<Self> {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?.port(port);
+        if let (Some(username), Some(password)) = (username, password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(message.to.parse()?)
+            .subject(message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body)?;
+
+        self.transport.send(email).await?;
+
+        Ok(())
+    }
+}
+
+/// Builds the mailer selected by `MAIL_TRANSPORT` (`log` or `smtp`).
+pub fn build_mailer(settings: &Settings) -> Result<Arc<dyn Mailer>> {
+    match settings.mail_transport.as_str() {
+        "log" => Ok(Arc::new(LogMailer::new(
+            settings.mail_outbox_path.as_ref().map(PathBuf::from),
+        ))),
+        "smtp" => {
+            let host = settings
+                .smtp_host
+                .as_deref()
+                .ok_or_else(|| anyhow!("SMTP_HOST must be set when MAIL_TRANSPORT=smtp"))?;
+            Ok(Arc::new(SmtpMailer::new(
+                host,
+                settings.smtp_port,
+                settings.smtp_username.clone(),
+                settings.smtp_password.clone(),
+                &settings.mail_from,
+            )?))
+        }
+        other => Err(anyhow!("Unknown mail transport: {}", other)),
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use sqlx::SqlitePool;
+use totp_rs::{Algorithm, TOTP};
+use uuid::Uuid;
+
+use crate::{
+    config::Settings,
+    models::{
+        mfa::{RecoveryCodeStatus, RecoveryCodes, TotpEnrollment, UserTotpRow},
+        user::User,
+    },
+    utils::{
+        crypto::{decrypt_secret, encrypt_secret},
+        password::hash_token,
+    },
+};
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+/// Number of steps either side of "now" accepted to tolerate clock drift.
+const TOTP_ALLOWED_DRIFT: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Random bytes per recovery code (80 bits, shown as four groups of five hex characters).
+const RECOVERY_CODE_BYTES: usize = 10;
+
+pub struct MfaService<'a> {
+    pool: &'a SqlitePool,
+    settings: &'a Settings,
+}
+
+impl<'a> MfaService<'a> {
+    pub fn new(pool: &'a SqlitePool, settings: &'a Settings) -> Self {
+        Self { pool, settings }
+    }
+
+    pub async fn is_enabled(&self, user_id: Uuid) -> Result<bool> {
+        let enabled: Option<bool> = sqlx::query_scalar(
+            "SELECT confirmed FROM user_totp WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(enabled.unwrap_or(false))
+    }
+
+    /// Records a pending second-factor login and returns its id, the mfa_token's `jti`.
+    pub async fn create_challenge(&self, user_id: Uuid, ttl: Duration) -> Result<Uuid> {
+        let now = Utc::now();
+        let challenge_id = Uuid::new_v4();
+
+        sqlx::query("DELETE FROM mfa_challenges WHERE expires_at <= ?")
+            .bind(now)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO mfa_challenges (id, user_id, expires_at, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(challenge_id)
+        .bind(user_id)
+        .bind(now + ttl)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        Ok(challenge_id)
+    }
+
+    /// Whether the challenge is still pending for this user.
+    pub async fn challenge_is_active(&self, challenge_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let active: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM mfa_challenges WHERE id = ? AND user_id = ? AND expires_at > ?",
+        )
+        .bind(challenge_id)
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(active.is_some())
+    }
+
+    /// Counts a wrong code against the challenge, discarding it after `MFA_MAX_ATTEMPTS`.
+    pub async fn record_challenge_failure(&self, challenge_id: Uuid) -> Result<()> {
+        let failed_attempts: Option<i64> = sqlx::query_scalar(
+            "UPDATE mfa_challenges SET failed_attempts = failed_attempts + 1 WHERE id = ? RETURNING failed_attempts",
+        )
+        .bind(challenge_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        if failed_attempts.is_some_and(|attempts| attempts >= self.settings.mfa_max_attempts) {
+            self.consume_challenge(challenge_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the challenge. Returns `false` if it was already consumed, so
+    /// only one of several concurrent verifications can complete the login.
+    pub async fn consume_challenge(&self, challenge_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM mfa_challenges WHERE id = ?")
+            .bind(challenge_id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Starts (or restarts) TOTP enrollment. The secret stays inactive until confirmed.
+    pub async fn enroll_totp(&self, user: &User) -> Result<TotpEnrollment> {
+        if self.is_enabled(user.id).await? {
+            return Err(anyhow!("Two-factor authentication is already enabled"));
+        }
+
+        let mut secret = vec![0u8; 20];
+        rand::thread_rng().fill_bytes(&mut secret);
+
+        let totp = self.build_totp(secret.clone(), &user.email)?;
+        let secret_encrypted = encrypt_secret(&self.settings.mfa_encryption_key, &secret)?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO user_totp (user_id, secret_encrypted, confirmed, last_used_step, created_at, confirmed_at)
+            VALUES (?, ?, FALSE, NULL, ?, NULL)
+            "#,
+        )
+        .bind(user.id)
+        .bind(&secret_encrypted)
+        .bind(Utc::now())
+        .execute(self.pool)
+        .await?;
+
+        Ok(TotpEnrollment {
+            secret: totp.get_secret_base32(),
+            otpauth_uri: totp.get_url(),
+        })
+    }
+
+    /// Activates a pending enrollment once the user proves their authenticator works.
+    /// Returns the initial set of recovery codes.
+    pub async fn confirm_totp(&self, user: &User, code: &str) -> Result<RecoveryCodes> {
+        let row = self
+            .get_totp_row(user.id)
+            .await?
+            .filter(|row| !row.confirmed)
+            .ok_or_else(|| anyhow!("No pending two-factor enrollment"))?;
+
+        let step = self
+            .match_code(&row, &user.email, code)?
+            .ok_or_else(|| anyhow!("Invalid authentication code"))?;
+
+        sqlx::query(
+            "UPDATE user_totp SET confirmed = TRUE, confirmed_at = ?, last_used_step = ? WHERE user_id = ?",
+        )
+        .bind(Utc::now())
+        .bind(step)
+        .bind(user.id)
+        .execute(self.pool)
+        .await?;
+
+        self.replace_recovery_codes(user.id).await
+    }
+
+    pub async fn disable_totp(&self, user: &User, code: &str) -> Result<()> {
+        if !self.verify_second_factor(user, code).await? {
+            return Err(anyhow!("Invalid authentication code"));
+        }
+
+        sqlx::query("DELETE FROM user_totp WHERE user_id = ?")
+            .bind(user.id)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+            .bind(user.id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Verifies either a TOTP code or a recovery code, consuming the latter.
+    pub async fn verify_second_factor(&self, user: &User, code: &str) -> Result<bool> {
+        let code = code.trim();
+        if code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit()) {
+            self.verify_totp(user, code).await
+        } else {
+            self.consume_recovery_code(user, code).await
+        }
+    }
+
+    /// Invalidates the current recovery codes and issues a new set.
+    pub async fn regenerate_recovery_codes(&self, user: &User, code: &str) -> Result<RecoveryCodes> {
+        if !self.verify_second_factor(user, code).await? {
+            return Err(anyhow!("Invalid authentication code"));
+        }
+
+        self.replace_recovery_codes(user.id).await
+    }
+
+    pub async fn recovery_code_status(&self, user_id: Uuid) -> Result<RecoveryCodeStatus> {
+        let (total, remaining, last_used_at): (i64, i64, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COUNT(*) - COUNT(used_at), MAX(used_at)
+            FROM mfa_recovery_codes
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(RecoveryCodeStatus { total, remaining, last_used_at })
+    }
+
+    async fn consume_recovery_code(&self, user: &User, code: &str) -> Result<bool> {
+        let code_hash = hash_token(&normalize_recovery_code(code));
+
+        // Marking the code used, rather than deleting it, keeps the history visible to the user
+        let result = sqlx::query(
+            "UPDATE mfa_recovery_codes SET used_at = ? WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+        )
+        .bind(Utc::now())
+        .bind(user.id)
+        .bind(&code_hash)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn replace_recovery_codes(&self, user_id: Uuid) -> Result<RecoveryCodes> {
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for code in &codes {
+            sqlx::query(
+                r#"
+                INSERT INTO mfa_recovery_codes (id, user_id, code_hash, created_at)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(hash_token(&normalize_recovery_code(code)))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(RecoveryCodes { codes })
+    }
+
+    /// Checks a code against the user's active authenticator. Each time step
+    /// can only be used once, so a captured code cannot be replayed.
+    pub async fn verify_totp(&self, user: &User, code: &str) -> Result<bool> {
+        let row = self
+            .get_totp_row(user.id)
+            .await?
+            .filter(|row| row.confirmed)
+            .ok_or_else(|| anyhow!("Two-factor authentication is not enabled"))?;
+
+        let Some(step) = self.match_code(&row, &user.email, code)? else {
+            return Ok(false);
+        };
+
+        // Conditional update so concurrent requests cannot both consume the same step
+        let result = sqlx::query(
+            r#"
+            UPDATE user_totp SET last_used_step = ?
+            WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)
+            "#,
+        )
+        .bind(step)
+        .bind(user.id)
+        .bind(step)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn get_totp_row(&self, user_id: Uuid) -> Result<Option<UserTotpRow>> {
+        let row = sqlx::query_as::<_, UserTotpRow>(
+            "SELECT secret_encrypted, confirmed, last_used_step FROM user_totp WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// Returns the time step the code is valid for, if any.
+    fn match_code(&self, row: &UserTotpRow, account_name: &str, code: &str) -> Result<Option<i64>> {
+        let secret = decrypt_secret(&self.settings.mfa_encryption_key, &row.secret_encrypted)?;
+        let totp = self.build_totp(secret, account_name)?;
+        let current_step = Utc::now().timestamp() / TOTP_STEP_SECONDS as i64;
+
+        let step = (-TOTP_ALLOWED_DRIFT..=TOTP_ALLOWED_DRIFT)
+            .map(|offset| current_step + offset)
+            .filter(|step| row.last_used_step.is_none_or(|last| *step > last))
+            .find(|step| totp.check(code, *step as u64 * TOTP_STEP_SECONDS));
+
+        Ok(step)
+    }
+
+    fn build_totp(&self, secret: Vec<u8>, account_name: &str) -> Result<TOTP> {
+        TOTP::new(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            0,
+            TOTP_STEP_SECONDS,
+            secret,
+            Some(self.settings.mfa_issuer.clone()),
+            account_name.to_string(),
+        )
+        .map_err(|e| anyhow!("Failed to build TOTP: {}", e))
+    }
+}
+
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let encoded = hex::encode(bytes);
+
+    encoded
+        .as_bytes()
+        .chunks(5)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Recovery codes are compared case-insensitively and ignoring separators.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
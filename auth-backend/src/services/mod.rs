@@ -1,3 +1,21 @@
 pub mod auth_service;
 pub mod user_service;
-pub mod admin_service;
\ No newline at end of file
+pub mod admin_service;
+pub mod mailer;
+pub mod verification_service;
+pub mod password_reset_service;
+pub mod mfa_service;
+pub mod passkey_service;
+pub mod session_service;
+pub mod token_revocation;
+pub mod lockout_service;
+pub mod login_event_service;
+pub mod audit_service;
+pub mod breached_passwords;
+pub mod password_history;
+pub mod hashing_pool;
+
+pub mod access_token_service;
+pub mod service_account_service;
+pub mod role_service;
+pub mod organization_service;
\ No newline at end of file
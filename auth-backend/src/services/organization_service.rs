@@ -0,0 +1,430 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        audit::AuditAction,
+        organization::{
+            AddMemberRequest, CreateOrganizationRequest, Membership, MembershipRow, OrgRole, Organization,
+            OrganizationMember, OrganizationMemberRow, UpdateOrganizationRequest, UserOrganization,
+            UserOrganizationRow,
+        },
+        session::ClientInfo,
+    },
+    services::audit_service::{record_audit_event, AuditService, NewAuditEvent},
+};
+
+pub struct OrganizationService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> OrganizationService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn membership(&self, organization_id: Uuid, user_id: Uuid) -> Result<Option<Membership>> {
+        let row = sqlx::query_as::<_, MembershipRow>(
+            "SELECT organization_id, user_id, role FROM memberships WHERE organization_id = ? AND user_id = ?",
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        row.map(Membership::try_from).transpose()
+    }
+
+    /// The organization the user joined first, used when nothing else is selected.
+    pub async fn default_membership(&self, user_id: Uuid) -> Result<Option<Membership>> {
+        let row = sqlx::query_as::<_, MembershipRow>(
+            r#"
+            SELECT organization_id, user_id, role FROM memberships
+            WHERE user_id = ?
+            ORDER BY created_at, organization_id
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        row.map(Membership::try_from).transpose()
+    }
+
+    /// The session's active organization, falling back to the default one when
+    /// none was selected or the user has since left it.
+    pub async fn session_membership(&self, session_id: Uuid, user_id: Uuid) -> Result<Option<Membership>> {
+        let active: Option<Option<Uuid>> =
+            sqlx::query_scalar("SELECT active_organization_id FROM sessions WHERE id = ? AND user_id = ?")
+                .bind(session_id)
+                .bind(user_id)
+                .fetch_optional(self.pool)
+                .await?;
+
+        if let Some(organization_id) = active.flatten() {
+            if let Some(membership) = self.membership(organization_id, user_id).await? {
+                return Ok(Some(membership));
+            }
+        }
+
+        self.default_membership(user_id).await
+    }
+
+    pub async fn set_active_organization(&self, session_id: Uuid, organization_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE sessions SET active_organization_id = ? WHERE id = ?")
+            .bind(organization_id)
+            .bind(session_id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_user_organizations(
+        &self,
+        user_id: Uuid,
+        active_organization_id: Option<Uuid>,
+    ) -> Result<Vec<UserOrganization>> {
+        let rows: Vec<UserOrganizationRow> = sqlx::query_as(
+            r#"
+            SELECT o.id, o.name, o.slug, o.created_by, o.created_at, o.updated_at, m.role
+            FROM memberships m
+            JOIN organizations o ON o.id = m.organization_id
+            WHERE m.user_id = ?
+            ORDER BY o.name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| row.into_user_organization(active_organization_id))
+            .collect()
+    }
+
+    /// Creates an organization with the creator as its owner.
+    pub async fn create_organization(
+        &self,
+        user_id: Uuid,
+        request: CreateOrganizationRequest,
+        client: &ClientInfo,
+    ) -> Result<Organization> {
+        let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM organizations WHERE slug = ?")
+            .bind(&request.slug)
+            .fetch_optional(self.pool)
+            .await?;
+        if exists.is_some() {
+            return Err(anyhow!("An organization with this slug already exists"));
+        }
+
+        let now = Utc::now();
+        let organization = Organization {
+            id: Uuid::new_v4(),
+            name: request.name,
+            slug: request.slug,
+            created_by: Some(user_id),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO organizations (id, name, slug, created_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(organization.id)
+        .bind(&organization.name)
+        .bind(&organization.slug)
+        .bind(user_id)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO memberships (organization_id, user_id, role, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(organization.id)
+        .bind(user_id)
+        .bind(OrgRole::Owner.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::OrganizationCreate,
+                actor_id: Some(user_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "organization_id": organization.id,
+                    "slug": organization.slug,
+                }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(organization)
+    }
+
+    pub async fn get_organization(&self, organization_id: Uuid) -> Result<Organization> {
+        sqlx::query_as::<_, Organization>(
+            "SELECT id, name, slug, created_by, created_at, updated_at FROM organizations WHERE id = ?",
+        )
+        .bind(organization_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Organization not found"))
+    }
+
+    pub async fn update_organization(
+        &self,
+        actor_id: Uuid,
+        organization_id: Uuid,
+        request: UpdateOrganizationRequest,
+        client: &ClientInfo,
+    ) -> Result<Organization> {
+        let result = sqlx::query("UPDATE organizations SET name = ?, updated_at = ? WHERE id = ?")
+            .bind(&request.name)
+            .bind(Utc::now())
+            .bind(organization_id)
+            .execute(self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Organization not found"));
+        }
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::OrganizationUpdate,
+                actor_id: Some(actor_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({ "organization_id": organization_id, "name": request.name }),
+            })
+            .await?;
+
+        self.get_organization(organization_id).await
+    }
+
+    /// Deletes the organization with its memberships. Sessions acting in it
+    /// fall back to the member's default organization on their next refresh.
+    pub async fn delete_organization(&self, actor_id: Uuid, organization_id: Uuid, client: &ClientInfo) -> Result<()> {
+        let organization = self.get_organization(organization_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM organizations WHERE id = ?")
+            .bind(organization_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::OrganizationDelete,
+                actor_id: Some(actor_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({ "organization_id": organization_id, "slug": organization.slug }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn list_members(&self, organization_id: Uuid) -> Result<Vec<OrganizationMember>> {
+        let rows: Vec<OrganizationMemberRow> = sqlx::query_as(
+            r#"
+            SELECT u.id AS user_id, u.email, u.name, m.role, m.created_at
+            FROM memberships m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = ?
+            ORDER BY u.email
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        rows.into_iter().map(OrganizationMember::try_from).collect()
+    }
+
+    /// Adds an existing account to the organization.
+    pub async fn add_member(
+        &self,
+        actor_id: Uuid,
+        organization_id: Uuid,
+        request: AddMemberRequest,
+        client: &ClientInfo,
+    ) -> Result<OrganizationMember> {
+        let user_id: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE email = ?")
+            .bind(&request.email)
+            .fetch_optional(self.pool)
+            .await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        if self.membership(organization_id, user_id).await?.is_some() {
+            return Err(anyhow!("User is already a member of this organization"));
+        }
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO memberships (organization_id, user_id, role, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .bind(request.role.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::OrganizationMemberAdd,
+                actor_id: Some(actor_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "organization_id": organization_id, "role": request.role }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_member(organization_id, user_id).await
+    }
+
+    pub async fn update_member(
+        &self,
+        actor_id: Uuid,
+        organization_id: Uuid,
+        user_id: Uuid,
+        role: OrgRole,
+        client: &ClientInfo,
+    ) -> Result<OrganizationMember> {
+        let member = self.get_member(organization_id, user_id).await?;
+        if member.role == OrgRole::Owner && role != OrgRole::Owner {
+            self.ensure_other_owner(organization_id, user_id).await?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE memberships SET role = ?, updated_at = ? WHERE organization_id = ? AND user_id = ?")
+            .bind(role.as_str())
+            .bind(Utc::now())
+            .bind(organization_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::OrganizationMemberUpdate,
+                actor_id: Some(actor_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "organization_id": organization_id, "role": role }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_member(organization_id, user_id).await
+    }
+
+    pub async fn remove_member(
+        &self,
+        actor_id: Uuid,
+        organization_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<()> {
+        let member = self.get_member(organization_id, user_id).await?;
+        if member.role == OrgRole::Owner {
+            self.ensure_other_owner(organization_id, user_id).await?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM memberships WHERE organization_id = ? AND user_id = ?")
+            .bind(organization_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::OrganizationMemberRemove,
+                actor_id: Some(actor_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "organization_id": organization_id }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_member(&self, organization_id: Uuid, user_id: Uuid) -> Result<OrganizationMember> {
+        let row: OrganizationMemberRow = sqlx::query_as(
+            r#"
+            SELECT u.id AS user_id, u.email, u.name, m.role, m.created_at
+            FROM memberships m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = ? AND m.user_id = ?
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Member not found"))?;
+
+        row.try_into()
+    }
+
+    /// Organizations must always keep an owner.
+    async fn ensure_other_owner(&self, organization_id: Uuid, user_id: Uuid) -> Result<()> {
+        let other_owners: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM memberships WHERE organization_id = ? AND role = ? AND user_id != ?",
+        )
+        .bind(organization_id)
+        .bind(OrgRole::Owner.as_str())
+        .bind(user_id)
+        .fetch_one(self.pool)
+        .await?;
+
+        if other_owners == 0 {
+            return Err(anyhow!("An organization cannot be left without an owner"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,380 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    config::Settings,
+    models::{
+        passkey::{
+            AuthenticationCredential, AuthenticatorSelection, FinishPasskeyRegistrationRequest,
+            Passkey, PublicKeyCredentialCreationOptions, PublicKeyCredentialDescriptor,
+            PublicKeyCredentialParameters, PublicKeyCredentialRequestOptions,
+            PublicKeyCredentialUser, RelyingParty, WebauthnCredentialRow,
+        },
+        user::{User, UserRow},
+    },
+    utils::webauthn::{
+        assertion_signed_data, base64url_decode, base64url_encode, parse_attestation_object,
+        parse_authenticator_data, parse_client_data, AuthenticatorData, CosePublicKey, COSE_ALG_EDDSA,
+        COSE_ALG_ES256, COSE_ALG_RS256, FLAG_USER_PRESENT, FLAG_USER_VERIFIED,
+    },
+};
+
+const CEREMONY_REGISTRATION: &str = "registration";
+const CEREMONY_AUTHENTICATION: &str = "authentication";
+const PUBLIC_KEY_TYPE: &str = "public-key";
+
+pub struct PasskeyService<'a> {
+    pool: &'a SqlitePool,
+    settings: &'a Settings,
+}
+
+impl<'a> PasskeyService<'a> {
+    pub fn new(pool: &'a SqlitePool, settings: &'a Settings) -> Self {
+        Self { pool, settings }
+    }
+
+    pub async fn start_registration(&self, user: &User) -> Result<PublicKeyCredentialCreationOptions> {
+        let challenge = self.create_challenge(Some(user.id), CEREMONY_REGISTRATION).await?;
+
+        // Stop the authenticator from creating a second passkey for this account
+        let exclude_credentials = self.credential_descriptors(user.id).await?;
+
+        Ok(PublicKeyCredentialCreationOptions {
+            challenge,
+            rp: RelyingParty {
+                id: self.settings.webauthn_rp_id.clone(),
+                name: self.settings.webauthn_rp_name.clone(),
+            },
+            user: PublicKeyCredentialUser {
+                id: base64url_encode(user.id.as_bytes()),
+                name: user.email.clone(),
+                display_name: user.name.clone().unwrap_or_else(|| user.email.clone()),
+            },
+            pub_key_cred_params: [COSE_ALG_ES256, COSE_ALG_EDDSA, COSE_ALG_RS256]
+                .into_iter()
+                .map(|alg| PublicKeyCredentialParameters {
+                    credential_type: PUBLIC_KEY_TYPE.to_string(),
+                    alg,
+                })
+                .collect(),
+            timeout: self.timeout_ms(),
+            attestation: "none".to_string(),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "preferred".to_string(),
+                user_verification: self.user_verification().to_string(),
+            },
+            exclude_credentials,
+        })
+    }
+
+    pub async fn finish_registration(
+        &self,
+        user: &User,
+        request: FinishPasskeyRegistrationRequest,
+    ) -> Result<Passkey> {
+        let credential = request.credential;
+        if credential.credential_type != PUBLIC_KEY_TYPE {
+            return Err(anyhow!("Invalid passkey credential type"));
+        }
+
+        let client_data_json = base64url_decode(&credential.response.client_data_json)?;
+        let client_data = parse_client_data(&client_data_json)?;
+        if client_data.ceremony_type != "webauthn.create" {
+            return Err(anyhow!("Invalid passkey ceremony"));
+        }
+        self.check_origin(&client_data.origin)?;
+
+        let challenge_user = self
+            .consume_challenge(&client_data.challenge, CEREMONY_REGISTRATION)
+            .await?;
+        if challenge_user != Some(user.id) {
+            return Err(anyhow!("Invalid or expired passkey challenge"));
+        }
+
+        let attestation_object = base64url_decode(&credential.response.attestation_object)?;
+        let auth_data = parse_attestation_object(&attestation_object)?;
+        self.check_authenticator_flags(&auth_data)?;
+
+        let attested = auth_data
+            .attested_credential
+            .ok_or_else(|| anyhow!("Passkey registration is missing credential data"))?;
+        if attested.credential_id != base64url_decode(&credential.raw_id)? {
+            return Err(anyhow!("Passkey credential ID mismatch"));
+        }
+
+        // Reject keys we would not be able to verify at login
+        CosePublicKey::from_cose(&attested.public_key)?;
+
+        let passkey = Passkey {
+            id: Uuid::new_v4(),
+            name: request.name.unwrap_or_else(|| "Passkey".to_string()),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO webauthn_credentials (id, user_id, credential_id, public_key, sign_count, name, transports, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(passkey.id)
+        .bind(user.id)
+        .bind(base64url_encode(&attested.credential_id))
+        .bind(&attested.public_key)
+        .bind(auth_data.sign_count as i64)
+        .bind(&passkey.name)
+        .bind(credential.response.transports.map(|t| t.join(",")))
+        .bind(passkey.created_at)
+        .execute(self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                anyhow!("Passkey is already registered")
+            }
+            other => other.into(),
+        })?;
+
+        Ok(passkey)
+    }
+
+    /// Begins a login ceremony. With an email the matching passkeys are listed;
+    /// without one the browser offers any discoverable passkey for this site.
+    pub async fn start_authentication(&self, email: Option<&str>) -> Result<PublicKeyCredentialRequestOptions> {
+        let user_row = match email {
+            Some(email) => sqlx::query_as::<_, UserRow>("SELECT * FROM users WHERE email = ?")
+                .bind(email)
+                .fetch_optional(self.pool)
+                .await?,
+            None => None,
+        };
+
+        let user_id = user_row.map(|row| row.id);
+        let challenge = self.create_challenge(user_id, CEREMONY_AUTHENTICATION).await?;
+
+        // Unknown emails get an empty list, same as a user without passkeys
+        let allow_credentials = match user_id {
+            Some(user_id) => self.credential_descriptors(user_id).await?,
+            None => Vec::new(),
+        };
+
+        Ok(PublicKeyCredentialRequestOptions {
+            challenge,
+            rp_id: self.settings.webauthn_rp_id.clone(),
+            timeout: self.timeout_ms(),
+            allow_credentials,
+            user_verification: self.user_verification().to_string(),
+        })
+    }
+
+    /// Verifies a login assertion and returns the ID of the authenticated user.
+    pub async fn finish_authentication(&self, credential: AuthenticationCredential) -> Result<Uuid> {
+        if credential.credential_type != PUBLIC_KEY_TYPE {
+            return Err(anyhow!("Invalid passkey credential type"));
+        }
+
+        let client_data_json = base64url_decode(&credential.response.client_data_json)?;
+        let client_data = parse_client_data(&client_data_json)?;
+        if client_data.ceremony_type != "webauthn.get" {
+            return Err(anyhow!("Invalid passkey ceremony"));
+        }
+        self.check_origin(&client_data.origin)?;
+
+        let challenge_user = self
+            .consume_challenge(&client_data.challenge, CEREMONY_AUTHENTICATION)
+            .await?;
+
+        let credential_id = base64url_encode(&base64url_decode(&credential.raw_id)?);
+        let stored = sqlx::query_as::<_, WebauthnCredentialRow>(
+            "SELECT id, user_id, public_key, sign_count FROM webauthn_credentials WHERE credential_id = ?",
+        )
+        .bind(&credential_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown passkey"))?;
+
+        if challenge_user.is_some_and(|user_id| user_id != stored.user_id) {
+            return Err(anyhow!("Unknown passkey"));
+        }
+        if let Some(user_handle) = &credential.response.user_handle {
+            if base64url_decode(user_handle)? != stored.user_id.as_bytes() {
+                return Err(anyhow!("Unknown passkey"));
+            }
+        }
+
+        let authenticator_data = base64url_decode(&credential.response.authenticator_data)?;
+        let auth_data = parse_authenticator_data(&authenticator_data)?;
+        self.check_authenticator_flags(&auth_data)?;
+
+        let signature = base64url_decode(&credential.response.signature)?;
+        CosePublicKey::from_cose(&stored.public_key)?.verify(
+            &assertion_signed_data(&authenticator_data, &client_data_json),
+            &signature,
+        )?;
+
+        // A counter that fails to advance suggests a cloned authenticator.
+        // Authenticators that do not implement counters always report zero.
+        let sign_count = auth_data.sign_count as i64;
+        if (sign_count != 0 || stored.sign_count != 0) && sign_count <= stored.sign_count {
+            tracing::warn!(credential = %stored.id, "Passkey sign counter did not increase");
+            return Err(anyhow!("Passkey counter check failed"));
+        }
+
+        sqlx::query("UPDATE webauthn_credentials SET sign_count = ?, last_used_at = ? WHERE id = ?")
+            .bind(sign_count)
+            .bind(Utc::now())
+            .bind(stored.id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(stored.user_id)
+    }
+
+    pub async fn list_passkeys(&self, user_id: Uuid) -> Result<Vec<Passkey>> {
+        let passkeys = sqlx::query_as::<_, Passkey>(
+            r#"
+            SELECT id, name, created_at, last_used_at
+            FROM webauthn_credentials
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(passkeys)
+    }
+
+    pub async fn rename_passkey(&self, user_id: Uuid, passkey_id: Uuid, name: &str) -> Result<()> {
+        let result = sqlx::query("UPDATE webauthn_credentials SET name = ? WHERE id = ? AND user_id = ?")
+            .bind(name)
+            .bind(passkey_id)
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Passkey not found"));
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_passkey(&self, user_id: Uuid, passkey_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM webauthn_credentials WHERE id = ? AND user_id = ?")
+            .bind(passkey_id)
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Passkey not found"));
+        }
+
+        Ok(())
+    }
+
+    async fn credential_descriptors(&self, user_id: Uuid) -> Result<Vec<PublicKeyCredentialDescriptor>> {
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT credential_id, transports FROM webauthn_credentials WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, transports)| PublicKeyCredentialDescriptor {
+                credential_type: PUBLIC_KEY_TYPE.to_string(),
+                id,
+                transports: transports.map(|t| t.split(',').map(str::to_string).collect()),
+            })
+            .collect())
+    }
+
+    async fn create_challenge(&self, user_id: Option<Uuid>, ceremony: &str) -> Result<String> {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let challenge = base64url_encode(&bytes);
+
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(self.settings.webauthn_challenge_ttl_minutes);
+
+        // Opportunistically clear out abandoned ceremonies
+        sqlx::query("DELETE FROM webauthn_challenges WHERE expires_at <= ?")
+            .bind(now)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO webauthn_challenges (challenge, user_id, ceremony, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&challenge)
+        .bind(user_id)
+        .bind(ceremony)
+        .bind(expires_at)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Deletes the challenge so it cannot be replayed, returning the user it was issued for.
+    async fn consume_challenge(&self, challenge: &str, ceremony: &str) -> Result<Option<Uuid>> {
+        let row: (Option<Uuid>,) = sqlx::query_as(
+            r#"
+            DELETE FROM webauthn_challenges
+            WHERE challenge = ? AND ceremony = ? AND expires_at > ?
+            RETURNING user_id
+            "#,
+        )
+        .bind(challenge)
+        .bind(ceremony)
+        .bind(Utc::now())
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Invalid or expired passkey challenge"))?;
+
+        Ok(row.0)
+    }
+
+    fn check_origin(&self, origin: &str) -> Result<()> {
+        if origin != self.settings.webauthn_origin {
+            return Err(anyhow!("Passkey origin mismatch"));
+        }
+        Ok(())
+    }
+
+    fn check_authenticator_flags(&self, auth_data: &AuthenticatorData) -> Result<()> {
+        if !auth_data.matches_rp_id(&self.settings.webauthn_rp_id) {
+            return Err(anyhow!("Passkey relying party mismatch"));
+        }
+        if !auth_data.has_flag(FLAG_USER_PRESENT) {
+            return Err(anyhow!("User presence is required"));
+        }
+        if self.settings.webauthn_require_user_verification && !auth_data.has_flag(FLAG_USER_VERIFIED) {
+            return Err(anyhow!("User verification is required"));
+        }
+        Ok(())
+    }
+
+    fn timeout_ms(&self) -> i64 {
+        self.settings.webauthn_challenge_ttl_minutes * 60 * 1000
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.settings.webauthn_require_user_verification {
+            "required"
+        } else {
+            "preferred"
+        }
+    }
+}
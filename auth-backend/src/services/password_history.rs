@@ -0,0 +1,115 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::SqliteConnection;
+use std::borrow::Cow;
+use uuid::Uuid;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::{services::hashing_pool::HashingPool, utils::validation::PasswordPolicyViolation};
+
+/// Remembers each user's last `size` password hashes, the current one included.
+/// Writes take a connection so they run inside the caller's transaction. The
+/// reuse check verifies hashes and belongs before that transaction, so the
+/// write lock is not held across it.
+pub struct PasswordHistory<'a> {
+    hashing_pool: &'a HashingPool,
+    size: usize,
+}
+
+impl<'a> PasswordHistory<'a> {
+    /// A size of 0 turns reuse checks off.
+    pub fn new(hashing_pool: &'a HashingPool, size: usize) -> Self {
+        Self { hashing_pool, size }
+    }
+
+    /// Fails with `PasswordPolicyViolation` (code `password_reused`) when the
+    /// password matches one of the remembered hashes.
+    pub async fn ensure_not_reused(
+        &self,
+        conn: &mut SqliteConnection,
+        user_id: Uuid,
+        field: &'static str,
+        password: &str,
+    ) -> Result<()> {
+        if self.size == 0 {
+            return Ok(());
+        }
+
+        let hashes: Vec<String> = sqlx::query_scalar(
+            "SELECT password_hash FROM password_history WHERE user_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(user_id)
+        .bind(self.size as i64)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for hash in hashes {
+            if self.hashing_pool.verify(password, &hash).await? {
+                let mut errors = ValidationErrors::new();
+                errors.add(
+                    field,
+                    ValidationError::new("password_reused").with_message(Cow::Owned(format!(
+                        "Password must differ from your last {} passwords",
+                        self.size
+                    ))),
+                );
+                return Err(PasswordPolicyViolation(errors).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remembers a newly set password hash and forgets everything beyond the last `size`.
+    pub async fn record(
+        &self,
+        conn: &mut SqliteConnection,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO password_history (id, user_id, password_hash, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(password_hash)
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM password_history
+            WHERE user_id = ? AND id NOT IN (
+                SELECT id FROM password_history WHERE user_id = ? ORDER BY created_at DESC LIMIT ?
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .bind(self.size.max(1) as i64)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Swaps a stored hash for a rehash of the same password, so the history
+    /// does not keep a copy made with weaker parameters.
+    pub async fn replace_hash(
+        &self,
+        conn: &mut SqliteConnection,
+        user_id: Uuid,
+        old_hash: &str,
+        new_hash: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE password_history SET password_hash = ? WHERE user_id = ? AND password_hash = ?")
+            .bind(new_hash)
+            .bind(user_id)
+            .bind(old_hash)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+}
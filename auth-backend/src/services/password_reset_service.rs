@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::Settings,
+    models::{audit::AuditAction, auth::ResetPasswordRequest, session::ClientInfo, user::UserRow},
+    services::{
+        access_token_service::delete_user_access_tokens,
+        audit_service::{record_audit_event, NewAuditEvent},
+        hashing_pool::HashingPool,
+        mailer::{EmailMessage, Mailer},
+        password_history::PasswordHistory,
+        session_service::SessionService,
+        token_revocation::TokenRevocationStore,
+    },
+    utils::{
+        password::{generate_token, hash_token},
+        validation::{with_password_violations, PasswordPolicy, PasswordPolicyViolation},
+    },
+};
+
+pub struct PasswordResetService<'a> {
+    pool: &'a SqlitePool,
+    mailer: &'a Arc<dyn Mailer>,
+    settings: &'a Settings,
+    revoked_tokens: &'a TokenRevocationStore,
+    hashing_pool: &'a HashingPool,
+}
+
+impl<'a> PasswordResetService<'a> {
+    pub fn new(
+        pool: &'a SqlitePool,
+        mailer: &'a Arc<dyn Mailer>,
+        settings: &'a Settings,
+        revoked_tokens: &'a TokenRevocationStore,
+        hashing_pool: &'a HashingPool,
+    ) -> Self {
+        Self { pool, mailer, settings, revoked_tokens, hashing_pool }
+    }
+
+    /// Emails a password reset link. Silently succeeds for unknown addresses so
+    /// the endpoint cannot be used to enumerate accounts.
+    pub async fn request_reset(&self, email: &str) -> Result<()> {
+        let user_row = sqlx::query_as::<_, UserRow>("SELECT * FROM users WHERE email = ?")
+            .bind(email)
+            .fetch_optional(self.pool)
+            .await?;
+
+        let Some(user_row) = user_row else {
+            return Ok(());
+        };
+
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::minutes(self.settings.password_reset_ttl_minutes);
+
+        // Only the most recently requested link stays valid
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
+            .bind(user_row.id)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_row.id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .bind(Utc::now())
+        .execute(self.pool)
+        .await?;
+
+        let link = format!(
+            "{}/reset-password?token={}",
+            self.settings.app_base_url.trim_end_matches('/'),
+            token
+        );
+
+        self.mailer
+            .send(EmailMessage {
+                to: user_row.email,
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "We received a request to reset your AuthFlow password.\n\nChoose a new password by opening the link below:\n\n{}\n\nThis link expires in {} minutes. If you did not request a reset, you can ignore this email.",
+                    link, self.settings.password_reset_ttl_minutes
+                ),
+            })
+            .await
+    }
+
+    /// Consumes a reset token, sets the new password and signs the user out everywhere.
+    pub async fn reset_password(&self, request: ResetPasswordRequest, client: &ClientInfo) -> Result<()> {
+        let token_hash = hash_token(&request.token);
+        let now = Utc::now();
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "SELECT user_id FROM password_reset_tokens WHERE token_hash = ? AND expires_at > ?",
+        )
+        .bind(&token_hash)
+        .bind(now)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Invalid or expired reset token"))?;
+
+        let user_row = sqlx::query_as::<_, UserRow>("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(self.pool)
+            .await?;
+        let personal_info = [user_row.email.as_str(), user_row.name.as_deref().unwrap_or_default()];
+        let violations = PasswordPolicy::from_settings(self.settings).check(&request.new_password, &personal_info);
+        if let Err(errors) = with_password_violations(Ok(()), "new_password", violations) {
+            return Err(PasswordPolicyViolation(errors).into());
+        }
+
+        // Hashing happens before the transaction so the write lock is never held across it
+        let password_history = PasswordHistory::new(self.hashing_pool, self.settings.password_history_size);
+        password_history
+            .ensure_not_reused(&mut *self.pool.acquire().await?, user_id, "new_password", &request.new_password)
+            .await?;
+
+        let password_hash = self.hashing_pool.hash(&request.new_password).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Deleting the row is what makes the token single-use; a concurrent reset may have won
+        let consumed: Option<Uuid> = sqlx::query_scalar(
+            "DELETE FROM password_reset_tokens WHERE token_hash = ? AND expires_at > ? RETURNING user_id",
+        )
+        .bind(&token_hash)
+        .bind(Utc::now())
+        .fetch_optional(&mut *tx)
+        .await?;
+        if consumed != Some(user_id) {
+            return Err(anyhow!("Invalid or expired reset token"));
+        }
+
+        // The user chose this password, so it also satisfies a forced change or expiry
+        sqlx::query(
+            "UPDATE users SET password_hash = ?, password_changed_at = ?, must_change_password = FALSE, updated_at = ? WHERE id = ?",
+        )
+        .bind(&password_hash)
+        .bind(now)
+        .bind(now)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        password_history.record(&mut tx, user_id, &password_hash).await?;
+
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // A reset often follows a takeover, so tokens the attacker minted must die with it
+        delete_user_access_tokens(&mut *tx, user_id).await?;
+
+        // Whoever held the emailed token acted as the user
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::PasswordReset,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({}),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        // Revoke every existing session, including access tokens still in flight
+        SessionService::new(self.pool, self.revoked_tokens)
+            .revoke_all_sessions(user_id)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,479 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::{Sqlite, SqliteExecutor, SqlitePool, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    audit::AuditAction,
+    role::{
+        CreatePermissionRequest, CreateRoleRequest, PermissionDefinition, Role, RoleRow,
+        SetUserRolesRequest, UpdateRoleRequest, UserRoles, ADMIN_ROLE, DEFAULT_ROLE,
+    },
+    session::ClientInfo,
+};
+use crate::services::audit_service::{record_audit_event, NewAuditEvent};
+
+/// Gives a new account the default role, in the same transaction that creates it.
+pub async fn assign_default_role<'e, E>(executor: E, user_id: Uuid) -> Result<()>
+where
+    E: SqliteExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO user_roles (user_id, role_id, created_at)
+        SELECT ?, id, ? FROM roles WHERE name = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(Utc::now())
+    .bind(DEFAULT_ROLE)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub struct RoleService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RoleService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// The user's roles and the union of their permissions, both sorted.
+    pub async fn user_roles(&self, user_id: Uuid) -> Result<UserRoles> {
+        let roles: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT roles.name
+            FROM user_roles
+            JOIN roles ON roles.id = user_roles.role_id
+            WHERE user_roles.user_id = ?
+            ORDER BY roles.name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        let permissions: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT permissions.name
+            FROM user_roles
+            JOIN role_permissions ON role_permissions.role_id = user_roles.role_id
+            JOIN permissions ON permissions.id = role_permissions.permission_id
+            WHERE user_roles.user_id = ?
+            ORDER BY permissions.name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(UserRoles { roles, permissions })
+    }
+
+    pub async fn list_roles(&self) -> Result<Vec<Role>> {
+        let rows = sqlx::query_as::<_, RoleRow>(
+            "SELECT id, name, description, built_in, created_at, updated_at FROM roles ORDER BY name",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        let grants: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT role_permissions.role_id, permissions.name
+            FROM role_permissions
+            JOIN permissions ON permissions.id = role_permissions.permission_id
+            ORDER BY permissions.name
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        let mut permissions_by_role: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (role_id, permission) in grants {
+            permissions_by_role.entry(role_id).or_default().push(permission);
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let permissions = permissions_by_role.remove(&row.id).unwrap_or_default();
+                row.into_role(permissions)
+            })
+            .collect())
+    }
+
+    pub async fn create_role(
+        &self,
+        admin_id: Uuid,
+        request: CreateRoleRequest,
+        client: &ClientInfo,
+    ) -> Result<Role> {
+        let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM roles WHERE name = ?")
+            .bind(&request.name)
+            .fetch_optional(self.pool)
+            .await?;
+        if exists.is_some() {
+            return Err(anyhow!("Role already exists"));
+        }
+
+        let role_id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO roles (id, name, description, built_in, created_at, updated_at)
+            VALUES (?, ?, ?, FALSE, ?, ?)
+            "#,
+        )
+        .bind(role_id)
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let permissions = set_role_permissions(&mut tx, role_id, &request.permissions).await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminRoleCreate,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "role_id": role_id,
+                    "name": request.name,
+                    "permissions": permissions,
+                }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_role(role_id).await
+    }
+
+    /// Replaces the role's description and permissions. The admin role's
+    /// permissions are fixed so it cannot lose access to role management.
+    pub async fn update_role(
+        &self,
+        admin_id: Uuid,
+        role_id: Uuid,
+        request: UpdateRoleRequest,
+        client: &ClientInfo,
+    ) -> Result<Role> {
+        let role = self.get_role(role_id).await?;
+        if role.name == ADMIN_ROLE {
+            return Err(anyhow!("The admin role's permissions cannot be changed"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE roles SET description = ?, updated_at = ? WHERE id = ?")
+            .bind(&request.description)
+            .bind(Utc::now())
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let permissions = set_role_permissions(&mut tx, role_id, &request.permissions).await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminRoleUpdate,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "role_id": role_id,
+                    "name": role.name,
+                    "permissions": permissions,
+                }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_role(role_id).await
+    }
+
+    pub async fn delete_role(&self, admin_id: Uuid, role_id: Uuid, client: &ClientInfo) -> Result<()> {
+        let role = self.get_role(role_id).await?;
+        if role.built_in {
+            return Err(anyhow!("Built-in roles cannot be deleted"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM roles WHERE id = ?")
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminRoleDelete,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({ "role_id": role_id, "name": role.name }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_role(&self, role_id: Uuid) -> Result<Role> {
+        let row = sqlx::query_as::<_, RoleRow>(
+            "SELECT id, name, description, built_in, created_at, updated_at FROM roles WHERE id = ?",
+        )
+        .bind(role_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Role not found"))?;
+
+        let permissions: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT permissions.name
+            FROM role_permissions
+            JOIN permissions ON permissions.id = role_permissions.permission_id
+            WHERE role_permissions.role_id = ?
+            ORDER BY permissions.name
+            "#,
+        )
+        .bind(role_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(row.into_role(permissions))
+    }
+
+    pub async fn list_permissions(&self) -> Result<Vec<PermissionDefinition>> {
+        let permissions = sqlx::query_as::<_, PermissionDefinition>(
+            "SELECT id, name, description, built_in, created_at FROM permissions ORDER BY name",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(permissions)
+    }
+
+    /// Custom permissions are not checked by this API; they exist so roles can
+    /// carry permissions for other services that read access token claims.
+    pub async fn create_permission(
+        &self,
+        admin_id: Uuid,
+        request: CreatePermissionRequest,
+        client: &ClientInfo,
+    ) -> Result<PermissionDefinition> {
+        let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM permissions WHERE name = ?")
+            .bind(&request.name)
+            .fetch_optional(self.pool)
+            .await?;
+        if exists.is_some() {
+            return Err(anyhow!("Permission already exists"));
+        }
+
+        let permission = PermissionDefinition {
+            id: Uuid::new_v4(),
+            name: request.name,
+            description: request.description,
+            built_in: false,
+            created_at: Utc::now(),
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO permissions (id, name, description, built_in, created_at) VALUES (?, ?, ?, FALSE, ?)",
+        )
+        .bind(permission.id)
+        .bind(&permission.name)
+        .bind(&permission.description)
+        .bind(permission.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminPermissionCreate,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({ "permission_id": permission.id, "name": permission.name }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(permission)
+    }
+
+    pub async fn delete_permission(&self, admin_id: Uuid, permission_id: Uuid, client: &ClientInfo) -> Result<()> {
+        let permission = sqlx::query_as::<_, PermissionDefinition>(
+            "SELECT id, name, description, built_in, created_at FROM permissions WHERE id = ?",
+        )
+        .bind(permission_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Permission not found"))?;
+
+        if permission.built_in {
+            return Err(anyhow!("Built-in permissions cannot be deleted"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM permissions WHERE id = ?")
+            .bind(permission_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminPermissionDelete,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({ "permission_id": permission_id, "name": permission.name }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Replaces the user's roles. `users.role` is kept for existing clients and
+    /// mirrors the primary role: `admin` if held, otherwise the first by name.
+    pub async fn set_user_roles(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        request: SetUserRolesRequest,
+        client: &ClientInfo,
+    ) -> Result<UserRoles> {
+        let user_exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(self.pool)
+            .await?;
+        if user_exists.is_none() {
+            return Err(anyhow!("User not found"));
+        }
+
+        let mut names = request.roles;
+        names.sort();
+        names.dedup();
+
+        if user_id == admin_id && !names.iter().any(|name| name == ADMIN_ROLE) {
+            return Err(anyhow!("You cannot remove the admin role from yourself"));
+        }
+
+        let mut role_ids = Vec::with_capacity(names.len());
+        for name in &names {
+            let role_id: Uuid = sqlx::query_scalar("SELECT id FROM roles WHERE name = ?")
+                .bind(name)
+                .fetch_optional(self.pool)
+                .await?
+                .ok_or_else(|| anyhow!("Unknown role: {}", name))?;
+            role_ids.push(role_id);
+        }
+
+        let primary_role = if names.iter().any(|name| name == ADMIN_ROLE) {
+            ADMIN_ROLE
+        } else {
+            names[0].as_str()
+        };
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for role_id in role_ids {
+            sqlx::query("INSERT INTO user_roles (user_id, role_id, created_at) VALUES (?, ?, ?)")
+                .bind(user_id)
+                .bind(role_id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE users SET role = ?, updated_at = ? WHERE id = ?")
+            .bind(primary_role)
+            .bind(now)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AdminSetUserRoles,
+                actor_id: Some(admin_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({ "roles": names }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.user_roles(user_id).await
+    }
+}
+
+/// Replaces a role's permissions, failing on unknown names. Returns the sorted names.
+async fn set_role_permissions(
+    tx: &mut Transaction<'_, Sqlite>,
+    role_id: Uuid,
+    permissions: &[String],
+) -> Result<Vec<String>> {
+    let mut names = permissions.to_vec();
+    names.sort();
+    names.dedup();
+
+    sqlx::query("DELETE FROM role_permissions WHERE role_id = ?")
+        .bind(role_id)
+        .execute(&mut **tx)
+        .await?;
+
+    for name in &names {
+        let permission_id: Uuid = sqlx::query_scalar("SELECT id FROM permissions WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or_else(|| anyhow!("Unknown permission: {}", name))?;
+
+        sqlx::query("INSERT INTO role_permissions (role_id, permission_id) VALUES (?, ?)")
+            .bind(role_id)
+            .bind(permission_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(names)
+}
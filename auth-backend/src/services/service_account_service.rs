@@ -0,0 +1,306 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        audit::AuditAction,
+        service_account::{
+            is_valid_scope, CreateServiceAccountRequest, ServiceAccount, ServiceAccountCredentials,
+            ServiceAccountRow, CLIENT_ID_PREFIX,
+        },
+        session::ClientInfo,
+    },
+    services::{
+        audit_service::{AuditService, NewAuditEvent},
+        token_revocation::TokenRevocationStore,
+    },
+    utils::{
+        jwt::ACCESS_TOKEN_TTL_MINUTES,
+        password::{generate_token, hash_token, secrets_match},
+    },
+};
+
+/// Client credentials grant failures, answered with the matching RFC 6749
+/// error code. Handlers downcast to it; anything else is a server error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientCredentialsError {
+    /// Unknown client or wrong secret; the two are not told apart
+    InvalidClient,
+    /// A requested scope was malformed or not granted to the client
+    InvalidScope(String),
+}
+
+impl fmt::Display for ClientCredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidClient => write!(f, "Client authentication failed"),
+            Self::InvalidScope(scope) => write!(f, "Scope '{}' is not granted to this client", scope),
+        }
+    }
+}
+
+impl std::error::Error for ClientCredentialsError {}
+
+pub struct ServiceAccountService<'a> {
+    pool: &'a SqlitePool,
+    revoked_tokens: &'a TokenRevocationStore,
+}
+
+impl<'a> ServiceAccountService<'a> {
+    pub fn new(pool: &'a SqlitePool, revoked_tokens: &'a TokenRevocationStore) -> Self {
+        Self { pool, revoked_tokens }
+    }
+
+    /// Creates a service account and returns its client secret, which is not stored.
+    pub async fn create_service_account(
+        &self,
+        admin_id: Uuid,
+        request: CreateServiceAccountRequest,
+        client: &ClientInfo,
+    ) -> Result<ServiceAccountCredentials> {
+        let client_secret = generate_token();
+        let now = Utc::now();
+
+        let mut scopes = request.scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let service_account = ServiceAccount {
+            id: Uuid::new_v4(),
+            name: request.name,
+            description: request.description,
+            client_id: format!("{}{}", CLIENT_ID_PREFIX, &generate_token()[..24]),
+            scopes,
+            created_by: Some(admin_id),
+            last_used_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO service_accounts
+                (id, name, description, client_id, client_secret_hash, scopes, created_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(service_account.id)
+        .bind(&service_account.name)
+        .bind(&service_account.description)
+        .bind(&service_account.client_id)
+        .bind(hash_token(&client_secret))
+        .bind(service_account.scopes.join(" "))
+        .bind(admin_id)
+        .bind(now)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::AdminServiceAccountCreate,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "service_account_id": service_account.id,
+                    "client_id": service_account.client_id,
+                    "scopes": service_account.scopes,
+                }),
+            })
+            .await?;
+
+        Ok(ServiceAccountCredentials { client_secret, service_account })
+    }
+
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccount>> {
+        let rows = sqlx::query_as::<_, ServiceAccountRow>(
+            r#"
+            SELECT id, name, description, client_id, client_secret_hash, scopes, created_by,
+                   last_used_at, created_at, updated_at
+            FROM service_accounts
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ServiceAccount::from).collect())
+    }
+
+    /// Replaces the client secret. Tokens issued with the old one stay valid until they expire.
+    pub async fn rotate_secret(
+        &self,
+        admin_id: Uuid,
+        service_account_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<ServiceAccountCredentials> {
+        let client_secret = generate_token();
+
+        let result = sqlx::query(
+            "UPDATE service_accounts SET client_secret_hash = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(hash_token(&client_secret))
+        .bind(Utc::now())
+        .bind(service_account_id)
+        .execute(self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Service account not found"));
+        }
+
+        let service_account = self.get_service_account(service_account_id).await?;
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::AdminServiceAccountRotateSecret,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "service_account_id": service_account.id,
+                    "client_id": service_account.client_id,
+                }),
+            })
+            .await?;
+
+        Ok(ServiceAccountCredentials { client_secret, service_account })
+    }
+
+    pub async fn delete_service_account(
+        &self,
+        admin_id: Uuid,
+        service_account_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<()> {
+        let service_account = self.get_service_account(service_account_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let issued: Vec<(Uuid, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT jti, expires_at FROM service_account_tokens WHERE service_account_id = ? AND expires_at > ?",
+        )
+        .bind(service_account_id)
+        .bind(Utc::now())
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Its issued tokens are deleted with it by the foreign key constraint
+        sqlx::query("DELETE FROM service_accounts WHERE id = ?")
+            .bind(service_account_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.revoked_tokens.revoke(&issued).await?;
+
+        AuditService::new(self.pool)
+            .record(NewAuditEvent {
+                action: AuditAction::AdminServiceAccountDelete,
+                actor_id: Some(admin_id),
+                target_id: None,
+                client,
+                metadata: serde_json::json!({
+                    "service_account_id": service_account.id,
+                    "client_id": service_account.client_id,
+                }),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records a client credentials token about to be issued, so deleting the
+    /// account can revoke it. Rows for expired tokens are purged here.
+    pub async fn record_token(&self, service_account_id: Uuid, token_id: Uuid) -> Result<()> {
+        let now = Utc::now();
+
+        sqlx::query("DELETE FROM service_account_tokens WHERE expires_at <= ?")
+            .bind(now)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query("INSERT INTO service_account_tokens (jti, service_account_id, expires_at) VALUES (?, ?, ?)")
+            .bind(token_id)
+            .bind(service_account_id)
+            .bind(now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES))
+            .execute(self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_service_account(&self, service_account_id: Uuid) -> Result<ServiceAccount> {
+        let row = sqlx::query_as::<_, ServiceAccountRow>(
+            r#"
+            SELECT id, name, description, client_id, client_secret_hash, scopes, created_by,
+                   last_used_at, created_at, updated_at
+            FROM service_accounts
+            WHERE id = ?
+            "#,
+        )
+        .bind(service_account_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Service account not found"))?;
+
+        Ok(ServiceAccount::from(row))
+    }
+
+    /// Checks the client secret and records the use. Fails with
+    /// `ClientCredentialsError::InvalidClient` for unknown clients and wrong secrets alike.
+    pub async fn authenticate(&self, client_id: &str, client_secret: &str) -> Result<ServiceAccount> {
+        let row = sqlx::query_as::<_, ServiceAccountRow>(
+            r#"
+            SELECT id, name, description, client_id, client_secret_hash, scopes, created_by,
+                   last_used_at, created_at, updated_at
+            FROM service_accounts
+            WHERE client_id = ?
+            "#,
+        )
+        .bind(client_id)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or(ClientCredentialsError::InvalidClient)?;
+
+        if !secrets_match(&row.client_secret_hash, &hash_token(client_secret)) {
+            return Err(ClientCredentialsError::InvalidClient.into());
+        }
+
+        let now = Utc::now();
+        sqlx::query("UPDATE service_accounts SET last_used_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(row.id)
+            .execute(self.pool)
+            .await?;
+
+        let mut service_account = ServiceAccount::from(row);
+        service_account.last_used_at = Some(now);
+        Ok(service_account)
+    }
+}
+
+/// The scopes to put in the token: the requested ones, which must all be
+/// granted to the client, or every granted scope when none are requested.
+pub fn granted_scope(service_account: &ServiceAccount, requested: Option<&str>) -> Result<String, ClientCredentialsError> {
+    let Some(requested) = requested.filter(|requested| !requested.trim().is_empty()) else {
+        return Ok(service_account.scopes.join(" "));
+    };
+
+    let mut scopes: Vec<&str> = requested.split_whitespace().collect();
+    if let Some(scope) = scopes
+        .iter()
+        .find(|scope| !is_valid_scope(scope) || !service_account.scopes.iter().any(|granted| granted == *scope))
+    {
+        return Err(ClientCredentialsError::InvalidScope(scope.to_string()));
+    }
+
+    scopes.sort_unstable();
+    scopes.dedup();
+    Ok(scopes.join(" "))
+}
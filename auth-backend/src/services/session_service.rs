@@ -0,0 +1,216 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    models::session::{ClientInfo, Session},
+    services::{access_token_service::delete_user_access_tokens, token_revocation::TokenRevocationStore},
+    utils::jwt::ACCESS_TOKEN_TTL_MINUTES,
+};
+
+pub struct SessionService<'a> {
+    pool: &'a SqlitePool,
+    revoked_tokens: &'a TokenRevocationStore,
+}
+
+impl<'a> SessionService<'a> {
+    pub fn new(pool: &'a SqlitePool, revoked_tokens: &'a TokenRevocationStore) -> Self {
+        Self { pool, revoked_tokens }
+    }
+
+    /// Records a new sign-in. The returned id doubles as the refresh-token family id.
+    pub async fn create_session(&self, user_id: Uuid, client: &ClientInfo) -> Result<Uuid> {
+        let session_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, device_name, user_agent, ip_address, created_at, last_used_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(describe_device(client.user_agent.as_deref()))
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .bind(now)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        Ok(session_id)
+    }
+
+    /// Called on every refresh so the session list shows recent activity.
+    pub async fn touch_session(&self, session_id: Uuid, client: &ClientInfo) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE sessions
+            SET last_used_at = ?, ip_address = COALESCE(?, ip_address)
+            WHERE id = ?
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(&client.ip_address)
+        .bind(session_id)
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists sessions that can still be refreshed, most recently used first.
+    pub async fn list_sessions(&self, user_id: Uuid, current_session_id: Uuid) -> Result<Vec<Session>> {
+        let mut sessions = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT s.id, s.device_name, s.user_agent, s.ip_address, s.created_at, s.last_used_at
+            FROM sessions s
+            WHERE s.user_id = ?
+              AND EXISTS (
+                  SELECT 1 FROM refresh_tokens rt
+                  WHERE rt.family_id = s.id AND rt.used_at IS NULL AND rt.expires_at > ?
+              )
+            ORDER BY s.last_used_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_all(self.pool)
+        .await?;
+
+        for session in &mut sessions {
+            session.current = session.id == current_session_id;
+        }
+
+        Ok(sessions)
+    }
+
+    /// Revokes one of the user's sessions. Returns `false` if it did not exist.
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool> {
+        let session_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM sessions WHERE id = ? AND user_id = ?",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(self.revoke_sessions(&session_ids, None).await? > 0)
+    }
+
+    /// Revokes every session of the user except `keep_session_id`, and all of
+    /// their personal access tokens. Returns how many sessions were revoked.
+    pub async fn revoke_other_sessions(&self, user_id: Uuid, keep_session_id: Uuid) -> Result<u64> {
+        let session_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM sessions WHERE user_id = ? AND id != ?",
+        )
+        .bind(user_id)
+        .bind(keep_session_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        self.revoke_sessions(&session_ids, Some(user_id)).await
+    }
+
+    /// Revokes all of the user's sessions and personal access tokens. Returns
+    /// how many sessions were revoked.
+    pub async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<u64> {
+        let session_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM sessions WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(self.pool)
+            .await?;
+
+        self.revoke_sessions(&session_ids, Some(user_id)).await
+    }
+
+    /// Deletes the sessions with their refresh tokens, and denylists any access
+    /// token they issued that has not expired yet. With `access_tokens_of`, that
+    /// user's personal access tokens are deleted in the same transaction.
+    async fn revoke_sessions(&self, session_ids: &[Uuid], access_tokens_of: Option<Uuid>) -> Result<u64> {
+        let issued_after = Utc::now() - Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+        let mut access_tokens = Vec::new();
+        let mut revoked = 0;
+
+        let mut tx = self.pool.begin().await?;
+
+        for session_id in session_ids {
+            let issued: Vec<(Uuid, DateTime<Utc>)> = sqlx::query_as(
+                r#"
+                SELECT access_jti, created_at FROM refresh_tokens
+                WHERE family_id = ? AND access_jti IS NOT NULL AND created_at > ?
+                "#,
+            )
+            .bind(session_id)
+            .bind(issued_after)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            access_tokens.extend(issued.into_iter().map(|(jti, issued_at)| {
+                (jti, issued_at + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES))
+            }));
+
+            sqlx::query("DELETE FROM refresh_tokens WHERE family_id = ?")
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+
+            let result = sqlx::query("DELETE FROM sessions WHERE id = ?")
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+
+            revoked += result.rows_affected();
+        }
+
+        if let Some(user_id) = access_tokens_of {
+            delete_user_access_tokens(&mut *tx, user_id).await?;
+        }
+
+        tx.commit().await?;
+
+        self.revoked_tokens.revoke(&access_tokens).await?;
+
+        Ok(revoked)
+    }
+}
+
+/// Builds a friendly name such as "Firefox on Linux" from a user agent string.
+fn describe_device(user_agent: Option<&str>) -> String {
+    let Some(ua) = user_agent else {
+        return "Unknown device".to_string();
+    };
+
+    // Order matters: Edge and Opera also claim Chrome, and Chrome also claims Safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+    ]
+    .iter()
+    .find(|(marker, _)| ua.contains(marker))
+    .map(|(_, name)| *name);
+
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ]
+    .iter()
+    .find(|(marker, _)| ua.contains(marker))
+    .map(|(_, name)| *name);
+
+    match (browser, os) {
+        (Some(browser), Some(os)) => format!("{} on {}", browser, os),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => "Unknown device".to_string(),
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// How often the cache is reloaded from the database and expired entries purged.
+/// Also bounds how long a revocation made by another instance takes to apply here.
+const SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Denylist of access token ids (`jti`) that must be rejected before they expire.
+///
+/// Lookups are served from memory so `AuthUser` does not pay a query per request.
+/// The `revoked_access_tokens` table is the source of truth and lets revocations
+/// survive restarts and reach other instances on the next sync.
+pub struct TokenRevocationStore {
+    pool: SqlitePool,
+    revoked: RwLock<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl TokenRevocationStore {
+    pub async fn load(pool: SqlitePool) -> Result<Self> {
+        let store = Self {
+            pool,
+            revoked: RwLock::new(HashMap::new()),
+        };
+        store.sync().await?;
+
+        Ok(store)
+    }
+
+    pub fn is_revoked(&self, jti: &Uuid) -> bool {
+        let revoked = self.revoked.read().unwrap_or_else(|e| e.into_inner());
+        revoked.get(jti).is_some_and(|expires_at| *expires_at > Utc::now())
+    }
+
+    /// Revokes access tokens given as `(jti, expires_at)` pairs.
+    pub async fn revoke(&self, tokens: &[(Uuid, DateTime<Utc>)]) -> Result<()> {
+        let now = Utc::now();
+
+        for (jti, expires_at) in tokens {
+            sqlx::query(
+                "INSERT OR IGNORE INTO revoked_access_tokens (jti, expires_at, revoked_at) VALUES (?, ?, ?)",
+            )
+            .bind(jti)
+            .bind(expires_at)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        let mut revoked = self.revoked.write().unwrap_or_else(|e| e.into_inner());
+        revoked.extend(tokens.iter().filter(|(_, expires_at)| *expires_at > now).copied());
+
+        Ok(())
+    }
+
+    /// Purges expired rows and entries, and picks up revocations made by other instances.
+    pub async fn sync(&self) -> Result<()> {
+        let now = Utc::now();
+
+        sqlx::query("DELETE FROM revoked_access_tokens WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        let rows: Vec<(Uuid, DateTime<Utc>)> =
+            sqlx::query_as("SELECT jti, expires_at FROM revoked_access_tokens")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut revoked = self.revoked.write().unwrap_or_else(|e| e.into_inner());
+        revoked.retain(|_, expires_at| *expires_at > now);
+        revoked.extend(rows);
+
+        Ok(())
+    }
+
+    /// Runs `sync` in the background for the lifetime of the process.
+    pub fn spawn_sync_task(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SYNC_INTERVAL);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.sync().await {
+                    tracing::error!("Access token revocation sync error: {:?}", e);
+                }
+            }
+        });
+    }
+}
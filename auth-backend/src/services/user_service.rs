@@ -1,22 +1,34 @@
 use anyhow::{anyhow, Result};
 use axum::extract::Multipart;
-use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
 use sqlx::SqlitePool;
 use std::path::Path;
 use uuid::Uuid;
 
-use crate::models::{
-    user::{User, UserRow, UpdateProfileRequest, ChangePasswordRequest},
+use crate::{
+    config::Settings,
+    models::{
+        audit::AuditAction,
+        session::ClientInfo,
+        user::{User, UserRow, UpdateProfileRequest, ChangePasswordRequest},
+    },
+    services::{
+        access_token_service::delete_user_access_tokens,
+        audit_service::{record_audit_event, NewAuditEvent},
+        hashing_pool::HashingPool,
+        password_history::PasswordHistory,
+    },
 };
 
 pub struct UserService<'a> {
     pool: &'a SqlitePool,
+    settings: &'a Settings,
+    hashing_pool: &'a HashingPool,
 }
 
 impl<'a> UserService<'a> {
-    pub fn new(pool: &'a SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pool: &'a SqlitePool, settings: &'a Settings, hashing_pool: &'a HashingPool) -> Self {
+        Self { pool, settings, hashing_pool }
     }
 
     pub async fn get_user_by_id(&self, user_id: &Uuid) -> Result<User> {
@@ -30,7 +42,12 @@ impl<'a> UserService<'a> {
         Ok(user_row.into())
     }
 
-    pub async fn update_profile(&self, user_id: Uuid, request: UpdateProfileRequest) -> Result<User> {
+    pub async fn update_profile(
+        &self,
+        user_id: Uuid,
+        request: UpdateProfileRequest,
+        client: &ClientInfo,
+    ) -> Result<User> {
         // Check if email is already taken by another user
         let existing_user = sqlx::query!(
             "SELECT id FROM users WHERE email = ? AND id != ?",
@@ -44,62 +61,150 @@ impl<'a> UserService<'a> {
             return Err(anyhow!("Email is already taken"));
         }
 
-        // Update user profile
+        let previous = self.get_user_by_id(&user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Update user profile; a new address has to be verified again
         sqlx::query(
-            "UPDATE users SET name = ?, email = ?, updated_at = ? WHERE id = ?",
+            r#"
+            UPDATE users
+            SET name = ?, email = ?, email_verified = email_verified AND email = ?, updated_at = ?
+            WHERE id = ?
+            "#,
         )
         .bind(&request.name)
         .bind(&request.email)
-        .bind(&Utc::now())
-        .bind(&user_id)
-        .execute(self.pool)
+        .bind(&request.email)
+        .bind(Utc::now())
+        .bind(user_id)
+        .execute(&mut *tx)
         .await?;
 
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::ProfileUpdate,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({
+                    "name": { "from": previous.name, "to": request.name },
+                    "email": { "from": previous.email, "to": request.email },
+                }),
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
         // Fetch updated user
         self.get_user_by_id(&user_id).await
     }
 
-    pub async fn change_password(&self, user_id: Uuid, request: ChangePasswordRequest) -> Result<()> {
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        request: ChangePasswordRequest,
+        client: &ClientInfo,
+    ) -> Result<()> {
         // Get current user
         let user_row = sqlx::query_as::<_, UserRow>(
             "SELECT * FROM users WHERE id = ?",
         )
-        .bind(&user_id)
+        .bind(user_id)
         .fetch_one(self.pool)
         .await?;
 
         // Verify current password
-        if !verify(&request.current_password, &user_row.password_hash)? {
+        if !self.hashing_pool.verify(&request.current_password, &user_row.password_hash).await? {
             return Err(anyhow!("Invalid current password"));
         }
 
+        // Verifying and hashing happen before the transaction, which only writes
+        let password_history = PasswordHistory::new(self.hashing_pool, self.settings.password_history_size);
+        password_history
+            .ensure_not_reused(&mut *self.pool.acquire().await?, user_id, "new_password", &request.new_password)
+            .await?;
+
         // Hash new password
-        let new_password_hash = hash(&request.new_password, DEFAULT_COST)?;
+        let new_password_hash = self.hashing_pool.hash(&request.new_password).await?;
 
-        // Update password
-        sqlx::query(
-            "UPDATE users SET password_hash = ?, updated_at = ? WHERE id = ?",
+        let mut tx = self.pool.begin().await?;
+
+        // Update password; this also satisfies a forced change or expiry. Matching the
+        // verified hash makes a concurrent change fail instead of being overwritten.
+        let now = Utc::now();
+        let result = sqlx::query(
+            "UPDATE users SET password_hash = ?, password_changed_at = ?, must_change_password = FALSE, updated_at = ? WHERE id = ? AND password_hash = ?",
         )
         .bind(&new_password_hash)
-        .bind(&Utc::now())
-        .bind(&user_id)
-        .execute(self.pool)
+        .bind(now)
+        .bind(now)
+        .bind(user_id)
+        .bind(&user_row.password_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Invalid current password"));
+        }
+
+        password_history.record(&mut tx, user_id, &new_password_hash).await?;
+
+        // Personal access tokens do not outlive the password they were created under
+        delete_user_access_tokens(&mut *tx, user_id).await?;
+
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::PasswordChange,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({}),
+            },
+        )
         .await?;
 
+        tx.commit().await?;
+
         Ok(())
     }
 
-    pub async fn delete_account(&self, user_id: Uuid) -> Result<()> {
+    pub async fn delete_account(&self, user_id: Uuid, client: &ClientInfo) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // Recorded first, while the actor's email can still be looked up
+        record_audit_event(
+            &mut *tx,
+            NewAuditEvent {
+                action: AuditAction::AccountDelete,
+                actor_id: Some(user_id),
+                target_id: Some(user_id),
+                client,
+                metadata: serde_json::json!({}),
+            },
+        )
+        .await?;
+
         // Delete user (refresh tokens will be deleted by foreign key constraint)
         sqlx::query("DELETE FROM users WHERE id = ?")
-            .bind(&user_id)
-            .execute(self.pool)
+            .bind(user_id)
+            .execute(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+
         Ok(())
     }
 
-    pub async fn upload_avatar(&self, user_id: Uuid, multipart: &mut Multipart) -> Result<String> {
+    pub async fn upload_avatar(
+        &self,
+        user_id: Uuid,
+        multipart: &mut Multipart,
+        client: &ClientInfo,
+    ) -> Result<String> {
         while let Some(field) = multipart.next_field().await.map_err(|e| anyhow!("Multipart error: {}", e))? {
             let name = field.name().unwrap_or("");
             
@@ -136,16 +241,32 @@ impl<'a> UserService<'a> {
 
                 let avatar_url = format!("/uploads/avatars/{}", new_filename);
 
+                let mut tx = self.pool.begin().await?;
+
                 // Update user avatar URL
                 sqlx::query(
                     "UPDATE users SET avatar_url = ?, updated_at = ? WHERE id = ?",
                 )
                 .bind(&avatar_url)
-                .bind(&Utc::now())
-                .bind(&user_id)
-                .execute(self.pool)
+                .bind(Utc::now())
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+
+                record_audit_event(
+                    &mut *tx,
+                    NewAuditEvent {
+                        action: AuditAction::AvatarUpdate,
+                        actor_id: Some(user_id),
+                        target_id: Some(user_id),
+                        client,
+                        metadata: serde_json::json!({ "avatar_url": avatar_url }),
+                    },
+                )
                 .await?;
 
+                tx.commit().await?;
+
                 return Ok(avatar_url);
             }
         }
@@ -153,10 +274,40 @@ impl<'a> UserService<'a> {
         Err(anyhow!("No avatar file found in request"))
     }
 
-    pub async fn list_users(&self) -> Result<Vec<User>> {
-        let user_rows = sqlx::query_as::<_, UserRow>("SELECT * FROM users ORDER BY created_at DESC")
-            .fetch_all(self.pool)
-            .await?;
+    /// Every user, or only the members of `organization_id` when given.
+    pub async fn list_users(&self, organization_id: Option<Uuid>) -> Result<Vec<User>> {
+        let user_rows = match organization_id {
+            Some(organization_id) => {
+                sqlx::query_as::<_, UserRow>(
+                    r#"
+                    SELECT users.* FROM users
+                    JOIN memberships ON memberships.user_id = users.id
+                    WHERE memberships.organization_id = ?
+                    ORDER BY users.created_at DESC
+                    "#,
+                )
+                .bind(organization_id)
+                .fetch_all(self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query_as::<_, UserRow>("SELECT * FROM users ORDER BY created_at DESC")
+                    .fetch_all(self.pool)
+                    .await?
+            }
+        };
         Ok(user_rows.into_iter().map(Into::into).collect())
     }
+
+    /// Makes every route except password change reject the user until they pick
+    /// a new password. Returns `false` if the user does not exist.
+    pub async fn require_password_change(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET must_change_password = TRUE, updated_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
 }
\ No newline at end of file
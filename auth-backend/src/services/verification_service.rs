@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::Settings,
+    models::user::{User, UserRow},
+    services::mailer::{EmailMessage, Mailer},
+    utils::password::{generate_token, hash_token},
+};
+
+pub struct VerificationService<'a> {
+    pool: &'a SqlitePool,
+    mailer: &'a Arc<dyn Mailer>,
+    settings: &'a Settings,
+}
+
+impl<'a> VerificationService<'a> {
+    pub fn new(pool: &'a SqlitePool, mailer: &'a Arc<dyn Mailer>, settings: &'a Settings) -> Self {
+        Self { pool, mailer, settings }
+    }
+
+    /// Issues a fresh verification token for the user and emails the link.
+    /// Any previously issued tokens for the user are invalidated.
+    pub async fn send_verification(&self, user: &User) -> Result<()> {
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::hours(self.settings.email_verification_ttl_hours);
+
+        sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = ?")
+            .bind(user.id)
+            .execute(self.pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO email_verification_tokens (id, user_id, token_hash, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .bind(Utc::now())
+        .execute(self.pool)
+        .await?;
+
+        let link = format!(
+            "{}/verify-email?token={}",
+            self.settings.app_base_url.trim_end_matches('/'),
+            token
+        );
+
+        self.mailer
+            .send(EmailMessage {
+                to: user.email.clone(),
+                subject: "Verify your email address".to_string(),
+                body: format!(
+                    "Welcome to AuthFlow!\n\nConfirm your email address by opening the link below:\n\n{}\n\nThis link expires in {} hours.",
+                    link, self.settings.email_verification_ttl_hours
+                ),
+            })
+            .await
+    }
+
+    /// Consumes a verification token and marks the owning user as verified.
+    pub async fn verify_email(&self, token: &str) -> Result<User> {
+        let token_hash = hash_token(token);
+        let now = Utc::now();
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "SELECT user_id FROM email_verification_tokens WHERE token_hash = ? AND expires_at > ?",
+        )
+        .bind(&token_hash)
+        .bind(now)
+        .fetch_optional(self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Invalid or expired verification token"))?;
+
+        sqlx::query("UPDATE users SET email_verified = ?, updated_at = ? WHERE id = ?")
+            .bind(true)
+            .bind(now)
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        // Tokens are single-use
+        sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = ?")
+            .bind(user_id)
+            .execute(self.pool)
+            .await?;
+
+        let user_row = sqlx::query_as::<_, UserRow>("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(self.pool)
+            .await?;
+
+        Ok(user_row.into())
+    }
+
+    /// Re-sends the verification email. Silently succeeds for unknown or already
+    /// verified addresses so the endpoint cannot be used to enumerate accounts.
+    pub async fn resend_verification(&self, email: &str) -> Result<()> {
+        let user_row = sqlx::query_as::<_, UserRow>("SELECT * FROM users WHERE email = ?")
+            .bind(email)
+            .fetch_optional(self.pool)
+            .await?;
+
+        match user_row {
+            Some(row) if !row.email_verified => self.send_verification(&row.into()).await,
+            _ => Ok(()),
+        }
+    }
+}
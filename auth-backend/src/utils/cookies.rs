@@ -0,0 +1,124 @@
+//! Session cookies for `AUTH_COOKIES` mode.
+//!
+//! The access and refresh tokens are HttpOnly, so injected scripts cannot read
+//! them. The CSRF token is deliberately readable: the SPA copies it into the
+//! `X-CSRF-Token` header, which another site cannot do (double-submit cookie).
+
+use axum::http::{HeaderMap, Method};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use time::Duration;
+
+use crate::{
+    config::{CookieSameSite, Settings},
+    models::auth::AuthResponse,
+    utils::password::{generate_token, hash_token},
+};
+
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+const ACCESS_TOKEN_PATH: &str = "/api";
+/// Keeps the refresh token away from every endpoint but refresh and logout
+const REFRESH_TOKEN_PATH: &str = "/api/auth";
+const CSRF_PATH: &str = "/";
+
+pub struct SessionCookies<'a> {
+    settings: &'a Settings,
+}
+
+impl<'a> SessionCookies<'a> {
+    pub fn new(settings: &'a Settings) -> Self {
+        Self { settings }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.auth_cookies
+    }
+
+    /// In cookie mode, moves the tokens out of the response body into cookies
+    /// and issues a fresh CSRF token. Otherwise returns both unchanged.
+    pub fn issue(&self, jar: CookieJar, mut auth: AuthResponse) -> (CookieJar, AuthResponse) {
+        if !self.enabled() {
+            return (jar, auth);
+        }
+
+        // Session-scoped logins get browser-session cookies
+        let session_max_age = auth
+            .persistent
+            .then(|| Duration::days(self.settings.refresh_token_persistent_ttl_days));
+        let access_token = std::mem::take(&mut auth.token);
+        let refresh_token = std::mem::take(&mut auth.refresh_token);
+
+        let jar = jar
+            .add(self.cookie(ACCESS_TOKEN_COOKIE, access_token, ACCESS_TOKEN_PATH, true, Some(Duration::seconds(auth.expires_in))))
+            .add(self.cookie(REFRESH_TOKEN_COOKIE, refresh_token, REFRESH_TOKEN_PATH, true, session_max_age))
+            .add(self.cookie(CSRF_COOKIE, generate_token(), CSRF_PATH, false, session_max_age));
+
+        (jar, auth)
+    }
+
+    /// Expires all session cookies in cookie mode.
+    pub fn clear(&self, jar: CookieJar) -> CookieJar {
+        if !self.enabled() {
+            return jar;
+        }
+
+        [
+            (ACCESS_TOKEN_COOKIE, ACCESS_TOKEN_PATH),
+            (REFRESH_TOKEN_COOKIE, REFRESH_TOKEN_PATH),
+            (CSRF_COOKIE, CSRF_PATH),
+        ]
+        .into_iter()
+        .fold(jar, |jar, (name, path)| {
+            jar.remove(self.cookie(name, String::new(), path, false, None))
+        })
+    }
+
+    fn cookie(
+        &self,
+        name: &'static str,
+        value: String,
+        path: &'static str,
+        http_only: bool,
+        max_age: Option<Duration>,
+    ) -> Cookie<'static> {
+        let mut cookie = Cookie::build((name, value))
+            .path(path)
+            .http_only(http_only)
+            .secure(self.settings.cookie_secure)
+            .same_site(match self.settings.cookie_same_site {
+                CookieSameSite::Strict => SameSite::Strict,
+                CookieSameSite::Lax => SameSite::Lax,
+                CookieSameSite::None => SameSite::None,
+            })
+            .build();
+
+        if let Some(domain) = &self.settings.cookie_domain {
+            cookie.set_domain(domain.clone());
+        }
+        if let Some(max_age) = max_age {
+            cookie.set_max_age(max_age);
+        }
+
+        cookie
+    }
+}
+
+/// Double-submit check for requests authenticated by cookie: the `X-CSRF-Token`
+/// header must repeat the CSRF cookie. Safe methods need no token.
+pub fn csrf_token_matches(method: &Method, headers: &HeaderMap, jar: &CookieJar) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+
+    let header = headers.get(CSRF_HEADER).and_then(|value| value.to_str().ok());
+    match (jar.get(CSRF_COOKIE), header) {
+        // Compared as digests so timing does not reveal how much of the token matched
+        (Some(cookie), Some(header)) if !cookie.value().is_empty() => {
+            hash_token(cookie.value()) == hash_token(header)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,42 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, Result};
+
+const NONCE_LEN: usize = 12;
+
+fn cipher(key_hex: &str) -> Result<Aes256Gcm> {
+    let key = hex::decode(key_hex)?;
+    if key.len() != 32 {
+        return Err(anyhow!("Encryption key must be 32 bytes (64 hex characters)"));
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// Encrypts `plaintext` with AES-256-GCM, returning hex-encoded `nonce || ciphertext`.
+pub fn encrypt_secret(key_hex: &str, plaintext: &[u8]) -> Result<String> {
+    let cipher = cipher(key_hex)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Failed to encrypt secret"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(hex::encode(sealed))
+}
+
+/// Reverses [`encrypt_secret`].
+pub fn decrypt_secret(key_hex: &str, sealed_hex: &str) -> Result<Vec<u8>> {
+    let cipher = cipher(key_hex)?;
+    let sealed = hex::decode(sealed_hex)?;
+    if sealed.len() <= NONCE_LEN {
+        return Err(anyhow!("Encrypted secret is malformed"));
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to decrypt secret"))
+}
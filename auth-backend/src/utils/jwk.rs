@@ -0,0 +1,179 @@
+//! Asymmetric signing keys for access tokens: PEM loading, JWK export and
+//! RFC 7638 thumbprints, which are used as `kid` values.
+
+use anyhow::{anyhow, Result};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{models::auth::JsonWebKey, utils::webauthn::base64url_encode};
+
+/// The public half of a signing key. The algorithm follows from the key type:
+/// RSA keys sign RS256, P-256 keys ES256 and Ed25519 keys EdDSA.
+#[derive(Debug, Clone)]
+pub enum PublicKeyMaterial {
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    P256 { x: Vec<u8>, y: Vec<u8> },
+    Ed25519 { x: Vec<u8> },
+}
+
+impl PublicKeyMaterial {
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Self::Rsa { .. } => Algorithm::RS256,
+            Self::P256 { .. } => Algorithm::ES256,
+            Self::Ed25519 { .. } => Algorithm::EdDSA,
+        }
+    }
+
+    pub fn decoding_key(&self) -> Result<DecodingKey> {
+        let key = match self {
+            Self::Rsa { n, e } => DecodingKey::from_rsa_raw_components(n, e),
+            Self::P256 { x, y } => {
+                DecodingKey::from_ec_components(&base64url_encode(x), &base64url_encode(y))?
+            }
+            Self::Ed25519 { x } => DecodingKey::from_ed_components(&base64url_encode(x))?,
+        };
+
+        Ok(key)
+    }
+
+    /// RFC 7638 thumbprint: SHA-256 over the required members in lexicographic order.
+    pub fn thumbprint(&self) -> String {
+        let canonical = match self {
+            Self::Rsa { n, e } => json!({
+                "e": base64url_encode(e),
+                "kty": "RSA",
+                "n": base64url_encode(n),
+            }),
+            Self::P256 { x, y } => json!({
+                "crv": "P-256",
+                "kty": "EC",
+                "x": base64url_encode(x),
+                "y": base64url_encode(y),
+            }),
+            Self::Ed25519 { x } => json!({
+                "crv": "Ed25519",
+                "kty": "OKP",
+                "x": base64url_encode(x),
+            }),
+        };
+
+        // serde_json orders object keys alphabetically and writes no whitespace
+        base64url_encode(&Sha256::digest(canonical.to_string().as_bytes()))
+    }
+
+    pub fn to_jwk(&self, kid: &str) -> JsonWebKey {
+        let mut jwk = JsonWebKey {
+            kty: String::new(),
+            kid: kid.to_string(),
+            alg: String::new(),
+            key_use: "sig".to_string(),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        };
+
+        match self {
+            Self::Rsa { n, e } => {
+                jwk.kty = "RSA".to_string();
+                jwk.alg = "RS256".to_string();
+                jwk.n = Some(base64url_encode(n));
+                jwk.e = Some(base64url_encode(e));
+            }
+            Self::P256 { x, y } => {
+                jwk.kty = "EC".to_string();
+                jwk.alg = "ES256".to_string();
+                jwk.crv = Some("P-256".to_string());
+                jwk.x = Some(base64url_encode(x));
+                jwk.y = Some(base64url_encode(y));
+            }
+            Self::Ed25519 { x } => {
+                jwk.kty = "OKP".to_string();
+                jwk.alg = "EdDSA".to_string();
+                jwk.crv = Some("Ed25519".to_string());
+                jwk.x = Some(base64url_encode(x));
+            }
+        }
+
+        jwk
+    }
+}
+
+/// Loads a private key (PKCS#8, or PKCS#1 / SEC1 for RSA / EC) for signing.
+pub fn load_private_key_pem(pem: &str) -> Result<(EncodingKey, PublicKeyMaterial)> {
+    use ed25519_dalek::pkcs8::DecodePrivateKey as _;
+    use p256::pkcs8::{EncodePrivateKey, LineEnding};
+    use rsa::{pkcs1::DecodeRsaPrivateKey, traits::PublicKeyParts};
+
+    let rsa_key = rsa::RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(pem));
+    if let Ok(key) = rsa_key {
+        let public = PublicKeyMaterial::Rsa {
+            n: key.n().to_bytes_be(),
+            e: key.e().to_bytes_be(),
+        };
+        return Ok((EncodingKey::from_rsa_pem(pem.as_bytes())?, public));
+    }
+
+    let ec_key = p256::SecretKey::from_pkcs8_pem(pem).or_else(|_| p256::SecretKey::from_sec1_pem(pem));
+    if let Ok(key) = ec_key {
+        let public = p256_material(&key.public_key());
+        // jsonwebtoken only accepts PKCS#8 for EC keys
+        let pkcs8 = key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|_| anyhow!("Failed to encode EC signing key"))?;
+        return Ok((EncodingKey::from_ec_pem(pkcs8.as_bytes())?, public));
+    }
+
+    if let Ok(key) = ed25519_dalek::SigningKey::from_pkcs8_pem(pem) {
+        let public = PublicKeyMaterial::Ed25519 {
+            x: key.verifying_key().to_bytes().to_vec(),
+        };
+        return Ok((EncodingKey::from_ed_pem(pem.as_bytes())?, public));
+    }
+
+    Err(anyhow!("Unsupported signing key: expected an RSA, P-256 or Ed25519 private key"))
+}
+
+/// Loads a verification-only key. Accepts public keys (SPKI, or PKCS#1 for RSA)
+/// as well as private keys, so a retired signing key file can be reused as is.
+pub fn load_public_key_pem(pem: &str) -> Result<PublicKeyMaterial> {
+    use ed25519_dalek::pkcs8::DecodePublicKey as _;
+    use rsa::{pkcs1::DecodeRsaPublicKey, traits::PublicKeyParts};
+
+    if pem.contains("PRIVATE KEY") {
+        return load_private_key_pem(pem).map(|(_, public)| public);
+    }
+
+    let rsa_key = rsa::RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(pem));
+    if let Ok(key) = rsa_key {
+        return Ok(PublicKeyMaterial::Rsa {
+            n: key.n().to_bytes_be(),
+            e: key.e().to_bytes_be(),
+        });
+    }
+
+    if let Ok(key) = p256::PublicKey::from_public_key_pem(pem) {
+        return Ok(p256_material(&key));
+    }
+
+    if let Ok(key) = ed25519_dalek::VerifyingKey::from_public_key_pem(pem) {
+        return Ok(PublicKeyMaterial::Ed25519 { x: key.to_bytes().to_vec() });
+    }
+
+    Err(anyhow!("Unsupported verification key: expected an RSA, P-256 or Ed25519 key"))
+}
+
+fn p256_material(key: &p256::PublicKey) -> PublicKeyMaterial {
+    use p256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let point = key.to_encoded_point(false);
+    PublicKeyMaterial::P256 {
+        x: point.x().map(|x| x.to_vec()).unwrap_or_default(),
+        y: point.y().map(|y| y.to_vec()).unwrap_or_default(),
+    }
+}
@@ -1,15 +1,49 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use uuid::Uuid;
 
-use crate::models::{
-    auth::{Claims, RefreshClaims},
-    user::User,
+use crate::{
+    config::Settings,
+    models::{
+        auth::{Claims, JsonWebKey, JsonWebKeySet, MfaClaims, RefreshClaims},
+        organization::Membership,
+        role::UserRoles,
+        service_account::ServiceAccount,
+        user::User,
+    },
+    utils::jwk::{load_private_key_pem, load_public_key_pem, PublicKeyMaterial},
 };
 
+/// Audience of the short-lived token handed out between the password and
+/// second-factor steps of login. Access token validation rejects it.
+const MFA_AUDIENCE: &str = "mfa";
+
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 10;
+
+struct SigningKey {
+    kid: String,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+struct VerificationKey {
+    kid: String,
+    algorithm: Algorithm,
+    key: DecodingKey,
+    public: PublicKeyMaterial,
+}
+
+/// Access tokens are signed with `access_secret` (HS256) unless an asymmetric
+/// signing key is configured, in which case they carry a `kid` and can be
+/// verified by anyone holding the published JWKS. Refresh and MFA tokens are
+/// only ever read by this service and stay HMAC-signed.
 pub struct JwtKeys {
     pub access_secret: String,
     pub refresh_secret: String,
+    signing_key: Option<SigningKey>,
+    /// The signing key's public half first, then keys kept for rotation
+    verification_keys: Vec<VerificationKey>,
 }
 
 impl JwtKeys {
@@ -17,17 +51,149 @@ impl JwtKeys {
         Self {
             access_secret,
             refresh_secret,
+            signing_key: None,
+            verification_keys: Vec::new(),
+        }
+    }
+
+    /// Builds the key set from settings, loading PEM keys from disk when configured.
+    pub fn from_settings(settings: &Settings) -> Result<Self> {
+        let mut keys = Self::new(settings.jwt_secret.clone(), settings.jwt_refresh_secret.clone());
+
+        let Some(path) = &settings.jwt_signing_key_path else {
+            return Ok(keys);
+        };
+
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read JWT signing key {}", path))?;
+        let (encoding_key, public) = load_private_key_pem(&pem)
+            .with_context(|| format!("Invalid JWT signing key {}", path))?;
+        let kid = public.thumbprint();
+
+        keys.signing_key = Some(SigningKey {
+            kid: kid.clone(),
+            algorithm: public.algorithm(),
+            key: encoding_key,
+        });
+        keys.add_verification_key(kid, public)?;
+
+        for path in &settings.jwt_verification_key_paths {
+            let pem = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read JWT verification key {}", path))?;
+            let public = load_public_key_pem(&pem)
+                .with_context(|| format!("Invalid JWT verification key {}", path))?;
+            keys.add_verification_key(public.thumbprint(), public)?;
+        }
+
+        Ok(keys)
+    }
+
+    fn add_verification_key(&mut self, kid: String, public: PublicKeyMaterial) -> Result<()> {
+        if self.verification_keys.iter().any(|key| key.kid == kid) {
+            return Ok(());
         }
+
+        self.verification_keys.push(VerificationKey {
+            kid,
+            algorithm: public.algorithm(),
+            key: public.decoding_key()?,
+            public,
+        });
+
+        Ok(())
     }
 
-    pub fn generate_access_token(&self, user: &User) -> Result<String> {
+    /// Public keys that verify access tokens, for `/.well-known/jwks.json`.
+    /// Empty when access tokens are HMAC-signed.
+    pub fn jwks(&self) -> JsonWebKeySet {
+        let keys: Vec<JsonWebKey> = self
+            .verification_keys
+            .iter()
+            .map(|key| key.public.to_jwk(&key.kid))
+            .collect();
+
+        JsonWebKeySet { keys }
+    }
+
+    pub fn generate_access_token(
+        &self,
+        user: &User,
+        user_roles: &UserRoles,
+        membership: Option<&Membership>,
+        session_id: Uuid,
+        token_id: Uuid,
+    ) -> Result<String> {
         let now = Utc::now();
-        let exp = now + Duration::minutes(10); // 10 minutes
+        let exp = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
 
         let claims = Claims {
             sub: user.id.to_string(),
+            sid: session_id.to_string(),
+            jti: token_id.to_string(),
             email: user.email.clone(),
             role: user.role.clone(),
+            roles: user_roles.roles.clone(),
+            permissions: user_roles.permissions.clone(),
+            org_id: membership.map(|membership| membership.organization_id.to_string()),
+            org_role: membership.map(|membership| membership.role),
+            client_id: None,
+            scope: None,
+            exp: exp.timestamp() as usize,
+            iat: now.timestamp() as usize,
+        };
+
+        self.sign_access_token(&claims)
+    }
+
+    /// Access token for the client credentials grant, signed like user access
+    /// tokens so other services verify both with the same keys.
+    pub fn generate_client_token(&self, account: &ServiceAccount, scope: &str, token_id: Uuid) -> Result<String> {
+        let now = Utc::now();
+        let exp = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+
+        let claims = Claims {
+            sub: account.client_id.clone(),
+            sid: String::new(),
+            jti: token_id.to_string(),
+            email: String::new(),
+            role: String::new(),
+            roles: Vec::new(),
+            permissions: Vec::new(),
+            org_id: None,
+            org_role: None,
+            client_id: Some(account.client_id.clone()),
+            scope: Some(scope.to_string()),
+            exp: exp.timestamp() as usize,
+            iat: now.timestamp() as usize,
+        };
+
+        self.sign_access_token(&claims)
+    }
+
+    fn sign_access_token(&self, claims: &Claims) -> Result<String> {
+        let token = match &self.signing_key {
+            Some(signing_key) => {
+                let mut header = Header::new(signing_key.algorithm);
+                header.kid = Some(signing_key.kid.clone());
+                encode(&header, claims, &signing_key.key)?
+            }
+            None => encode(
+                &Header::default(),
+                claims,
+                &EncodingKey::from_secret(self.access_secret.as_ref()),
+            )?,
+        };
+
+        Ok(token)
+    }
+
+    pub fn generate_refresh_token(&self, user: &User, token_id: Uuid, ttl: Duration) -> Result<String> {
+        let now = Utc::now();
+        let exp = now + ttl;
+
+        let claims = RefreshClaims {
+            sub: user.id.to_string(),
+            jti: token_id.to_string(),
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
         };
@@ -35,18 +201,27 @@ impl JwtKeys {
         let token = encode(
             &Header::default(),
             &claims,
-            &EncodingKey::from_secret(self.access_secret.as_ref()),
+            &EncodingKey::from_secret(self.refresh_secret.as_ref()),
         )?;
 
         Ok(token)
     }
 
-    pub fn generate_refresh_token(&self, user: &User) -> Result<String> {
+    pub fn generate_mfa_token(
+        &self,
+        user: &User,
+        remember_me: bool,
+        ttl: Duration,
+        challenge_id: Uuid,
+    ) -> Result<String> {
         let now = Utc::now();
-        let exp = now + Duration::days(7); // 7 days
+        let exp = now + ttl;
 
-        let claims = RefreshClaims {
+        let claims = MfaClaims {
             sub: user.id.to_string(),
+            jti: challenge_id.to_string(),
+            aud: MFA_AUDIENCE.to_string(),
+            remember_me,
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
         };
@@ -54,22 +229,52 @@ impl JwtKeys {
         let token = encode(
             &Header::default(),
             &claims,
-            &EncodingKey::from_secret(self.refresh_secret.as_ref()),
+            &EncodingKey::from_secret(self.access_secret.as_ref()),
         )?;
 
         Ok(token)
     }
 
-    pub fn verify_access_token(&self, token: &str) -> Result<Claims> {
-        let token_data = decode::<Claims>(
+    pub fn verify_mfa_token(&self, token: &str) -> Result<MfaClaims> {
+        let mut validation = Validation::default();
+        validation.set_audience(&[MFA_AUDIENCE]);
+
+        let token_data = decode::<MfaClaims>(
             token,
             &DecodingKey::from_secret(self.access_secret.as_ref()),
-            &Validation::default(),
+            &validation,
         )?;
 
         Ok(token_data.claims)
     }
 
+    pub fn verify_access_token(&self, token: &str) -> Result<Claims> {
+        if self.signing_key.is_none() {
+            let token_data = decode::<Claims>(
+                token,
+                &DecodingKey::from_secret(self.access_secret.as_ref()),
+                &Validation::default(),
+            )?;
+
+            return Ok(token_data.claims);
+        }
+
+        // Only the configured keys are accepted, so an HMAC token (including
+        // one forged with a public key as the secret) never verifies here
+        let kid = decode_header(token)?
+            .kid
+            .ok_or_else(|| anyhow!("Token has no key id"))?;
+        let key = self
+            .verification_keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .ok_or_else(|| anyhow!("Unknown signing key"))?;
+
+        let token_data = decode::<Claims>(token, &key.key, &Validation::new(key.algorithm))?;
+
+        Ok(token_data.claims)
+    }
+
     pub fn verify_refresh_token(&self, token: &str) -> Result<RefreshClaims> {
         let token_data = decode::<RefreshClaims>(
             token,
@@ -1,3 +1,7 @@
+pub mod cookies;
+pub mod crypto;
+pub mod jwk;
 pub mod jwt;
 pub mod password;
-pub mod validation;
\ No newline at end of file
+pub mod validation;
+pub mod webauthn;
\ No newline at end of file
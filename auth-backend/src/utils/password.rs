@@ -1,7 +1,118 @@
+use anyhow::{anyhow, Result};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use rand::RngCore;
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::config::{PasswordHashAlgorithm, Settings};
 
 pub fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(token.as_bytes());
     format!("{:x}", hasher.finalize())
-}
\ No newline at end of file
+}
+
+/// Compares a secret or its digest without returning early at the first
+/// difference, so timing does not reveal how much of it matched.
+pub fn secrets_match(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Generates a random, URL-safe opaque token (256 bits, hex encoded).
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hashes new passwords with the configured algorithm and verifies hashes made
+/// by either supported one, so bcrypt hashes keep working after switching to Argon2id.
+pub struct PasswordHasher {
+    algorithm: PasswordHashAlgorithm,
+    argon2: Argon2<'static>,
+    bcrypt_cost: u32,
+}
+
+impl PasswordHasher {
+    pub fn from_settings(settings: &Settings) -> Result<Self> {
+        let params = Params::new(
+            settings.argon2_memory_kib,
+            settings.argon2_iterations,
+            settings.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| anyhow!("Invalid Argon2 parameters: {}", e))?;
+
+        Ok(Self {
+            algorithm: settings.password_hash_algorithm,
+            argon2: Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+            bcrypt_cost: settings.bcrypt_cost,
+        })
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String> {
+        match self.algorithm {
+            PasswordHashAlgorithm::Argon2id => {
+                let salt = SaltString::generate(&mut OsRng);
+                let hash = self
+                    .argon2
+                    .hash_password(password.as_bytes(), &salt)
+                    .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
+                Ok(hash.to_string())
+            }
+            PasswordHashAlgorithm::Bcrypt => Ok(bcrypt::hash(password, self.bcrypt_cost)?),
+        }
+    }
+
+    /// Accepts Argon2 PHC strings and bcrypt (`$2a$`, `$2b$`, `$2y$`) hashes.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        if is_bcrypt(hash) {
+            return Ok(bcrypt::verify(password, hash)?);
+        }
+
+        let parsed = PasswordHash::new(hash).map_err(|e| anyhow!("Unrecognised password hash: {}", e))?;
+        // Parameters come from the hash itself, not the current configuration
+        match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(anyhow!("Failed to verify password: {}", e)),
+        }
+    }
+
+    /// Whether a hash was made with another algorithm or different parameters
+    /// than new hashes would be.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        match self.algorithm {
+            PasswordHashAlgorithm::Bcrypt => bcrypt_cost(hash) != Some(self.bcrypt_cost),
+            PasswordHashAlgorithm::Argon2id => {
+                let Ok(parsed) = PasswordHash::new(hash) else {
+                    return true;
+                };
+                let current = self.argon2.params();
+
+                parsed.algorithm != Algorithm::Argon2id.ident()
+                    || parsed.version != Some(Version::V0x13.into())
+                    || Params::try_from(&parsed).map_or(true, |params| {
+                        params.m_cost() != current.m_cost()
+                            || params.t_cost() != current.t_cost()
+                            || params.p_cost() != current.p_cost()
+                    })
+            }
+        }
+    }
+}
+
+fn is_bcrypt(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix))
+}
+
+/// The cost field of a `$2b$12$...` hash.
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    if !is_bcrypt(hash) {
+        return None;
+    }
+    hash.get(4..6)?.parse().ok()
+}
@@ -1,27 +1,143 @@
-use validator::ValidationError;
+use std::{borrow::Cow, fmt};
 
-pub fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
-    let mut score = 0;
-    
-    if password.len() >= 8 {
-        score += 1;
-    }
-    if password.chars().any(|c| c.is_lowercase()) {
-        score += 1;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::config::Settings;
+
+/// Personal values shorter than this are too common to reject passwords over.
+const MIN_PERSONAL_TOKEN_LENGTH: usize = 3;
+
+/// The password rules configured through the `PASSWORD_*` settings.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub banned_words: Vec<String>,
+}
+
+impl PasswordPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            min_length: settings.password_min_length,
+            max_length: settings.password_max_length,
+            require_lowercase: settings.password_require_lowercase,
+            require_uppercase: settings.password_require_uppercase,
+            require_digit: settings.password_require_digit,
+            require_symbol: settings.password_require_symbol,
+            banned_words: settings.password_banned_words.clone(),
+        }
     }
-    if password.chars().any(|c| c.is_uppercase()) {
-        score += 1;
+
+    /// Returns one error per violated rule. `personal_info` is the user's email,
+    /// name and similar values the password must not contain.
+    pub fn check(&self, password: &str, personal_info: &[&str]) -> Vec<ValidationError> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            violations.push(rule_error(
+                "password_too_short",
+                format!("Password must be at least {} characters long", self.min_length),
+            ));
+        }
+        if length > self.max_length {
+            violations.push(rule_error(
+                "password_too_long",
+                format!("Password must be at most {} characters long", self.max_length),
+            ));
+        }
+
+        let classes = [
+            (self.require_lowercase, password.chars().any(char::is_lowercase), "password_missing_lowercase", "a lowercase letter"),
+            (self.require_uppercase, password.chars().any(char::is_uppercase), "password_missing_uppercase", "an uppercase letter"),
+            (self.require_digit, password.chars().any(|c| c.is_ascii_digit()), "password_missing_digit", "a digit"),
+            (self.require_symbol, password.chars().any(|c| !c.is_alphanumeric()), "password_missing_symbol", "a symbol"),
+        ];
+        for (required, present, code, description) in classes {
+            if required && !present {
+                violations.push(rule_error(code, format!("Password must contain {}", description)));
+            }
+        }
+
+        let lowered = password.to_lowercase();
+        if self
+            .banned_words
+            .iter()
+            .any(|word| !word.is_empty() && lowered.contains(&word.to_lowercase()))
+        {
+            violations.push(rule_error(
+                "password_banned_word",
+                "Password contains a commonly used word".to_string(),
+            ));
+        }
+
+        if personal_tokens(personal_info).any(|token| lowered.contains(&token)) {
+            violations.push(rule_error(
+                "password_contains_personal_info",
+                "Password must not contain your email address or name".to_string(),
+            ));
+        }
+
+        violations
     }
-    if password.chars().any(|c| c.is_numeric()) {
-        score += 1;
+}
+
+/// Adds password policy violations for `field` to the result of `Validate::validate`,
+/// so handlers report both in the same `details` object.
+pub fn with_password_violations(
+    result: Result<(), ValidationErrors>,
+    field: &'static str,
+    violations: Vec<ValidationError>,
+) -> Result<(), ValidationErrors> {
+    let mut errors = result.err().unwrap_or_default();
+    for violation in violations {
+        errors.add(field, violation);
     }
-    if password.chars().any(|c| "!@#$%^&*()_+-=[]{}|;:,.<>?".contains(c)) {
-        score += 1;
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
     }
-    
-    if score < 4 {
-        return Err(ValidationError::new("weak_password"));
+}
+
+/// Returned by services that only learn whose password it is after the request
+/// was validated. Handlers downcast to it to report the per-rule details.
+#[derive(Debug)]
+pub struct PasswordPolicyViolation(pub ValidationErrors);
+
+impl fmt::Display for PasswordPolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Password does not meet the password policy")
     }
-    
-    Ok(())
-}
\ No newline at end of file
+}
+
+impl std::error::Error for PasswordPolicyViolation {}
+
+fn rule_error(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::Owned(message))
+}
+
+/// Splits emails and names into the lowercase fragments worth matching:
+/// the whole value, the email's local part and each word of a name.
+fn personal_tokens<'a>(personal_info: &'a [&'a str]) -> impl Iterator<Item = String> + 'a {
+    personal_info
+        .iter()
+        .flat_map(|value| {
+            let value = value.trim().to_lowercase();
+            let local_part = value.split('@').next().unwrap_or_default().to_string();
+            let words: Vec<String> = value
+                .split(|c: char| !c.is_alphanumeric())
+                .map(String::from)
+                .collect();
+
+            std::iter::once(value.clone())
+                .chain(std::iter::once(local_part))
+                .chain(if value.contains('@') { Vec::new() } else { words })
+        })
+        .filter(|token| token.chars().count() >= MIN_PERSONAL_TOKEN_LENGTH)
+}
@@ -0,0 +1,227 @@
+//! Minimal WebAuthn (Level 2) primitives: client data and authenticator data
+//! parsing, COSE public keys and assertion signature verification.
+//!
+//! Attestation statements are not verified; registration requests `"none"`
+//! attestation, which is what platform passkeys provide anyway.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::Value;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+pub const COSE_ALG_ES256: i64 = -7;
+pub const COSE_ALG_EDDSA: i64 = -8;
+pub const COSE_ALG_RS256: i64 = -257;
+
+pub const FLAG_USER_PRESENT: u8 = 0x01;
+pub const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+pub fn base64url_encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Decodes base64url, tolerating trailing padding some clients still send.
+pub fn base64url_decode(value: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| anyhow!("Invalid base64url encoding"))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectedClientData {
+    #[serde(rename = "type")]
+    pub ceremony_type: String,
+    pub challenge: String,
+    pub origin: String,
+}
+
+pub fn parse_client_data(client_data_json: &[u8]) -> Result<CollectedClientData> {
+    serde_json::from_slice(client_data_json).map_err(|_| anyhow!("Invalid client data"))
+}
+
+#[derive(Debug)]
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE_Key encoded public key, stored verbatim
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+
+    pub fn matches_rp_id(&self, rp_id: &str) -> bool {
+        self.rp_id_hash.as_slice() == Sha256::digest(rp_id.as_bytes()).as_slice()
+    }
+}
+
+pub fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData> {
+    if data.len() < 37 {
+        return Err(anyhow!("Authenticator data is too short"));
+    }
+
+    let mut rp_id_hash = [0u8; 32];
+    rp_id_hash.copy_from_slice(&data[..32]);
+    let flags = data[32];
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+        // aaguid (16) | credential id length (2) | credential id | COSE key
+        let rest = &data[37..];
+        if rest.len() < 18 {
+            return Err(anyhow!("Attested credential data is truncated"));
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_len {
+            return Err(anyhow!("Attested credential data is truncated"));
+        }
+        let (credential_id, mut key_bytes) = rest.split_at(id_len);
+
+        // Decode exactly one CBOR item to find where the COSE key ends
+        let before = key_bytes.len();
+        let _: Value = ciborium::de::from_reader(&mut key_bytes)
+            .map_err(|_| anyhow!("Invalid credential public key"))?;
+        let consumed = before - key_bytes.len();
+
+        Some(AttestedCredential {
+            credential_id: credential_id.to_vec(),
+            public_key: rest[id_len..id_len + consumed].to_vec(),
+        })
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData {
+        rp_id_hash,
+        flags,
+        sign_count,
+        attested_credential,
+    })
+}
+
+/// Extracts `authData` from a CBOR attestation object.
+pub fn parse_attestation_object(attestation_object: &[u8]) -> Result<AuthenticatorData> {
+    let value: Value = ciborium::de::from_reader(attestation_object)
+        .map_err(|_| anyhow!("Invalid attestation object"))?;
+
+    let auth_data = value
+        .as_map()
+        .and_then(|entries| {
+            entries.iter().find_map(|(key, value)| match key.as_text() {
+                Some("authData") => value.as_bytes(),
+                _ => None,
+            })
+        })
+        .ok_or_else(|| anyhow!("Attestation object is missing authData"))?;
+
+    parse_authenticator_data(auth_data)
+}
+
+#[derive(Debug)]
+pub enum CosePublicKey {
+    Es256 { x: Vec<u8>, y: Vec<u8> },
+    EdDsa { x: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CosePublicKey {
+    pub fn from_cose(bytes: &[u8]) -> Result<Self> {
+        let value: Value =
+            ciborium::de::from_reader(bytes).map_err(|_| anyhow!("Invalid COSE key"))?;
+        let entries = value.as_map().ok_or_else(|| anyhow!("Invalid COSE key"))?;
+
+        let int_param = |label: i64| {
+            entries.iter().find_map(|(key, value)| {
+                (key.as_integer() == Some(label.into()))
+                    .then(|| value.as_integer().and_then(|v| i64::try_from(v).ok()))
+                    .flatten()
+            })
+        };
+        let bytes_param = |label: i64| {
+            entries
+                .iter()
+                .find_map(|(key, value)| {
+                    (key.as_integer() == Some(label.into()))
+                        .then(|| value.as_bytes().cloned())
+                        .flatten()
+                })
+                .ok_or_else(|| anyhow!("COSE key is missing parameter {}", label))
+        };
+
+        match int_param(3) {
+            Some(COSE_ALG_ES256) => Ok(Self::Es256 {
+                x: bytes_param(-2)?,
+                y: bytes_param(-3)?,
+            }),
+            Some(COSE_ALG_EDDSA) => Ok(Self::EdDsa { x: bytes_param(-2)? }),
+            Some(COSE_ALG_RS256) => Ok(Self::Rs256 {
+                n: bytes_param(-1)?,
+                e: bytes_param(-2)?,
+            }),
+            _ => Err(anyhow!("Unsupported public key algorithm")),
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let invalid = |_| anyhow!("Invalid passkey signature");
+
+        match self {
+            Self::Es256 { x, y } => {
+                use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(anyhow!("Invalid P-256 public key"));
+                }
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    x.as_slice().into(),
+                    y.as_slice().into(),
+                    false,
+                );
+                let key = VerifyingKey::from_encoded_point(&point)
+                    .map_err(|_| anyhow!("Invalid P-256 public key"))?;
+                let signature = Signature::from_der(signature).map_err(invalid)?;
+                key.verify(message, &signature).map_err(invalid)
+            }
+            Self::EdDsa { x } => {
+                use ed25519_dalek::{Signature, VerifyingKey};
+
+                let x: [u8; 32] = x
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid Ed25519 public key"))?;
+                let key = VerifyingKey::from_bytes(&x)
+                    .map_err(|_| anyhow!("Invalid Ed25519 public key"))?;
+                let signature = Signature::from_slice(signature).map_err(invalid)?;
+                key.verify_strict(message, &signature).map_err(invalid)
+            }
+            Self::Rs256 { n, e } => {
+                use rsa::{pkcs1v15, signature::Verifier, BigUint, RsaPublicKey};
+
+                let key = RsaPublicKey::new(BigUint::from_bytes_be(n), BigUint::from_bytes_be(e))
+                    .map_err(|_| anyhow!("Invalid RSA public key"))?;
+                let verifier = pkcs1v15::VerifyingKey::<Sha256>::new(key);
+                let signature = pkcs1v15::Signature::try_from(signature).map_err(invalid)?;
+                verifier.verify(message, &signature).map_err(invalid)
+            }
+        }
+    }
+}
+
+/// The byte string an authenticator signs during an assertion.
+pub fn assertion_signed_data(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    let mut signed = authenticator_data.to_vec();
+    signed.extend_from_slice(&Sha256::digest(client_data_json));
+    signed
+}
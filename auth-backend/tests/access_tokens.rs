@@ -0,0 +1,188 @@
+//! Personal access tokens: scopes, and revocation alongside the user's password and sessions.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Duration, Utc};
+use common::{link_token, TestApp, NEW_PASSWORD, PASSWORD};
+use serde_json::json;
+
+async fn assert_token_works(app: &TestApp, token: &str) {
+    let (status, body) = app.request(Method::GET, "/api/auth/me", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+async fn assert_token_rejected(app: &TestApp, token: &str) {
+    let (status, _) = app.request(Method::GET, "/api/auth/me", Some(token), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_read_token_cannot_write() {
+    let app = TestApp::new().await;
+    let (_, session) = app.register("scopes@example.com").await;
+    let profile = json!({ "name": "Renamed", "email": "scopes@example.com" });
+
+    let read = app.create_access_token(&session, &["read"]).await;
+    let (status, body) = app.request(Method::GET, "/api/users/profile", Some(&read), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.request(Method::PUT, "/api/users/profile", Some(&read), Some(profile.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "insufficient_scope");
+    assert_eq!(body["details"]["required"], "write");
+
+    // `write` implies `read`
+    let write = app.create_access_token(&session, &["write"]).await;
+    let (status, body) = app.request(Method::PUT, "/api/users/profile", Some(&write), Some(profile)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.request(Method::GET, "/api/users/profile", Some(&write), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // No scope reaches the routes that manage credentials
+    let (status, body) = app
+        .request(Method::POST, "/api/users/tokens", Some(&write), Some(json!({ "name": "more", "scopes": ["read"] })))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "session_required");
+}
+
+#[tokio::test]
+async fn administrative_routes_need_the_admin_scope() {
+    let app = TestApp::new().await;
+    let (user_id, _) = app.register("operator@example.com").await;
+    app.make_admin(user_id).await;
+    let (_, data) = app.login("operator@example.com", PASSWORD).await;
+    let session = data["token"].as_str().unwrap();
+
+    let without_admin = app.create_access_token(session, &["read", "write"]).await;
+    let (status, body) = app.request(Method::GET, "/api/admin/audit", Some(&without_admin), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "insufficient_scope");
+    assert_eq!(body["details"]["required"], "admin");
+
+    let admin = app.create_access_token(session, &["read", "admin"]).await;
+    let (status, body) = app.request(Method::GET, "/api/admin/audit", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // The scope does not stand in for the role
+    let (_, session) = app.register("scopes@example.com").await;
+    let admin = app.create_access_token(&session, &["read", "admin"]).await;
+    let (status, body) = app.request(Method::GET, "/api/admin/audit", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "permission_denied");
+}
+
+#[tokio::test]
+async fn password_reset_deletes_access_tokens() {
+    let app = TestApp::new().await;
+    let (_, session) = app.register("reset@example.com").await;
+    let token = app.create_access_token(&session, &["read"]).await;
+    assert_token_works(&app, &token).await;
+
+    let (status, _) = app
+        .request(Method::POST, "/api/auth/forgot-password", None, Some(json!({ "email": "reset@example.com" })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let reset_token = link_token(app.mailer.sent_to("reset@example.com").last().unwrap());
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/auth/reset-password",
+            None,
+            Some(json!({ "token": reset_token, "new_password": NEW_PASSWORD, "confirm_password": NEW_PASSWORD })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_token_rejected(&app, &token).await;
+}
+
+#[tokio::test]
+async fn password_change_deletes_access_tokens() {
+    let app = TestApp::new().await;
+    let (_, session) = app.register("change@example.com").await;
+    let token = app.create_access_token(&session, &["read"]).await;
+    assert_token_works(&app, &token).await;
+
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            "/api/users/password",
+            Some(&session),
+            Some(json!({ "current_password": PASSWORD, "new_password": NEW_PASSWORD, "confirm_password": NEW_PASSWORD })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_token_rejected(&app, &token).await;
+    // The session that changed the password stays signed in
+    assert_token_works(&app, &session).await;
+}
+
+#[tokio::test]
+async fn signing_out_other_devices_deletes_access_tokens() {
+    let app = TestApp::new().await;
+    let (_, session) = app.register("others@example.com").await;
+    let token = app.create_access_token(&session, &["read"]).await;
+    assert_token_works(&app, &token).await;
+
+    let (status, body) = app
+        .request(Method::POST, "/api/users/sessions/revoke-others", Some(&session), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_token_rejected(&app, &token).await;
+    assert_token_works(&app, &session).await;
+}
+
+#[tokio::test]
+async fn admin_sign_out_deletes_access_tokens() {
+    let app = TestApp::new().await;
+    let (admin_id, _) = app.register("operator@example.com").await;
+    app.make_admin(admin_id).await;
+    let (_, admin_session) = app.login("operator@example.com", PASSWORD).await;
+    let admin_session = admin_session["token"].as_str().unwrap();
+
+    let (user_id, session) = app.register("target@example.com").await;
+    let token = app.create_access_token(&session, &["read"]).await;
+    assert_token_works(&app, &token).await;
+
+    let (status, body) = app
+        .request(Method::POST, &format!("/api/admin/users/{}/revoke-sessions", user_id), Some(admin_session), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_token_rejected(&app, &token).await;
+    assert_token_rejected(&app, &session).await;
+}
+
+#[tokio::test]
+async fn last_used_at_is_written_at_most_once_a_minute() {
+    let app = TestApp::new().await;
+    let (_, session) = app.register("usage@example.com").await;
+    let token = app.create_access_token(&session, &["read"]).await;
+
+    let last_used_at = || async {
+        sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT last_used_at FROM personal_access_tokens")
+            .fetch_one(app.pool())
+            .await
+            .unwrap()
+    };
+
+    assert_token_works(&app, &token).await;
+    let first_use = last_used_at().await.expect("first use is recorded");
+
+    assert_token_works(&app, &token).await;
+    assert_eq!(last_used_at().await, Some(first_use));
+
+    let stale = Utc::now() - Duration::minutes(2);
+    sqlx::query("UPDATE personal_access_tokens SET last_used_at = ?")
+        .bind(stale)
+        .execute(app.pool())
+        .await
+        .unwrap();
+
+    assert_token_works(&app, &token).await;
+    assert!(last_used_at().await.unwrap() > stale);
+}
@@ -0,0 +1,114 @@
+//! Loading and searching both layouts of the breached password list.
+
+mod common;
+
+use auth_backend::services::breached_passwords::BreachedPasswords;
+use sha1::{Digest, Sha1};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+const BREACHED: [(&str, u64); 3] = [("password", 9_000_000), ("letmein", 500_000), ("correcthorse", 2)];
+
+/// A temporary file or directory that is removed when dropped.
+struct Corpus(PathBuf);
+
+impl Drop for Corpus {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn temp_path() -> PathBuf {
+    std::env::temp_dir().join(format!("auth-backend-breached-{}", Uuid::new_v4()))
+}
+
+/// The breached hashes plus filler lines, sorted as in the download.
+fn lines() -> Vec<(String, u64)> {
+    let mut lines: Vec<_> = BREACHED
+        .iter()
+        .map(|(password, count)| (hex::encode_upper(Sha1::digest(password)), *count))
+        .chain((0..2000u32).map(|i| (hex::encode_upper(Sha1::digest(i.to_be_bytes())), 1)))
+        .collect();
+    lines.sort();
+    lines
+}
+
+fn single_file(lines: &[(String, u64)]) -> Corpus {
+    let path = temp_path();
+    let contents: String = lines.iter().map(|(hash, count)| format!("{}:{}\r\n", hash, count)).collect();
+    std::fs::write(&path, contents).unwrap();
+    Corpus(path)
+}
+
+fn ranges(lines: &[(String, u64)]) -> Corpus {
+    // The prefixes the loader samples, each with a line of its own
+    let mut files: BTreeMap<String, Vec<String>> = (0..=16u32)
+        .map(|sample| (format!("{:05X}", 0xFFFFF * sample / 16), vec![format!("{}:1", "0".repeat(35))]))
+        .collect();
+    for (hash, count) in lines {
+        let (prefix, suffix) = hash.split_at(5);
+        files.entry(prefix.to_string()).or_default().push(format!("{}:{}", suffix, count));
+    }
+
+    let path = temp_path();
+    std::fs::create_dir(&path).unwrap();
+    for (prefix, mut lines) in files {
+        lines.sort();
+        lines.dedup();
+        std::fs::write(range_file(&path, &prefix), lines.join("\r\n")).unwrap();
+    }
+    Corpus(path)
+}
+
+fn range_file(directory: &Path, prefix: &str) -> PathBuf {
+    directory.join(format!("{}.txt", prefix))
+}
+
+fn load(corpus: &Corpus, min_count: u64) -> anyhow::Result<BreachedPasswords> {
+    let mut settings = common::test_settings();
+    settings.breached_passwords_path = Some(corpus.0.to_string_lossy().into_owned());
+    settings.breached_passwords_min_count = min_count;
+    BreachedPasswords::load(&settings)
+}
+
+#[test]
+fn finds_breached_passwords_in_either_layout() {
+    let lines = lines();
+
+    for corpus in [single_file(&lines), ranges(&lines)] {
+        let breached = load(&corpus, 1).unwrap();
+        for (password, count) in BREACHED {
+            assert_eq!(breached.occurrences(password), count, "{}", password);
+        }
+        assert_eq!(breached.occurrences("not in the list"), 0);
+
+        let breached = load(&corpus, 3).unwrap();
+        assert!(breached.is_breached("password"));
+        assert!(!breached.is_breached("correcthorse"));
+    }
+}
+
+#[test]
+fn refuses_an_unsorted_single_file() {
+    let mut lines = lines();
+    lines.reverse();
+
+    let error = load(&single_file(&lines), 1).err().unwrap();
+    assert!(format!("{:#}", error).contains("not in ascending hash order"), "{:#}", error);
+}
+
+#[test]
+fn refuses_range_lines_in_a_single_file_and_an_incomplete_range_directory() {
+    let lines: Vec<_> = lines().into_iter().map(|(hash, count)| (hash[5..].to_string(), count)).collect();
+    let error = load(&single_file(&lines), 1).err().unwrap();
+    assert!(format!("{:#}", error).contains("not in <hash>:<count> format"), "{:#}", error);
+
+    let corpus = ranges(&[]);
+    std::fs::remove_file(range_file(&corpus.0, "FFFFF")).unwrap();
+    let error = load(&corpus, 1).err().unwrap();
+    assert!(format!("{:#}", error).contains("FFFFF.txt is missing"), "{:#}", error);
+}
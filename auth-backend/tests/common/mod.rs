@@ -0,0 +1,229 @@
+//! Shared setup for the integration tests: a fresh database, the real router
+//! and a mailer that keeps what it sends.
+
+// Each test binary uses a different subset of the helpers
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use auth_backend::{
+    config::{RateLimit, Settings},
+    routes,
+    services::{
+        breached_passwords::BreachedPasswords,
+        hashing_pool::HashingPool,
+        mailer::{EmailMessage, Mailer},
+        token_revocation::TokenRevocationStore,
+    },
+    utils::jwt::JwtKeys,
+    AppState,
+};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, Once},
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+pub const PASSWORD: &str = "Sup3r-Secret-Pass!9";
+pub const NEW_PASSWORD: &str = "An0ther-Secret-Pass!7";
+
+#[derive(Default)]
+pub struct RecordingMailer {
+    sent: Mutex<Vec<EmailMessage>>,
+}
+
+impl RecordingMailer {
+    pub fn sent_to(&self, to: &str) -> Vec<EmailMessage> {
+        let sent = self.sent.lock().unwrap();
+        sent.iter().filter(|message| message.to == to).cloned().collect()
+    }
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, message: EmailMessage) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+pub struct TestApp {
+    pub state: AppState,
+    pub mailer: Arc<RecordingMailer>,
+    router: Router,
+    database: PathBuf,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_settings(|_| {}).await
+    }
+
+    /// Starts from `Settings::new()` with cheap password hashing and no rate
+    /// limits, then applies `configure`.
+    pub async fn with_settings(configure: impl FnOnce(&mut Settings)) -> Self {
+        let mut settings = test_settings();
+        configure(&mut settings);
+
+        // A file rather than `:memory:`, so services that hold a transaction
+        // while another query runs get a second connection
+        let database = std::env::temp_dir().join(format!("auth-backend-test-{}.db", Uuid::new_v4()));
+        let pool = SqlitePoolOptions::new()
+            .connect_with(SqliteConnectOptions::new().filename(&database).create_if_missing(true))
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = AppState {
+            jwt_keys: Arc::new(JwtKeys::from_settings(&settings).unwrap()),
+            revoked_tokens: Arc::new(TokenRevocationStore::load(pool.clone()).await.unwrap()),
+            breached_passwords: Arc::new(BreachedPasswords::disabled()),
+            hashing_pool: Arc::new(HashingPool::start(&settings).unwrap()),
+            mailer: mailer.clone(),
+            settings: Arc::new(settings),
+            pool,
+        };
+
+        Self { router: routes::app(state.clone()), state, mailer, database }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.state.pool
+    }
+
+    /// Sends a JSON request with an optional bearer token and returns the
+    /// status and the decoded body (`Value::Null` when empty).
+    pub async fn request(&self, method: Method, path: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+        (status, body)
+    }
+
+    /// Registers a user with `PASSWORD` and returns its id and access token.
+    pub async fn register(&self, email: &str) -> (Uuid, String) {
+        let (status, body) = self
+            .request(
+                Method::POST,
+                "/api/auth/register",
+                None,
+                Some(serde_json::json!({
+                    "email": email,
+                    "password": PASSWORD,
+                    "confirm_password": PASSWORD,
+                    "name": "Test User",
+                    "agree_to_terms": true,
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+        let user_id = body["data"]["user"]["id"].as_str().unwrap().parse().unwrap();
+        (user_id, body["data"]["token"].as_str().unwrap().to_string())
+    }
+
+    /// Logs in and returns the response's `data`.
+    pub async fn login(&self, email: &str, password: &str) -> (StatusCode, Value) {
+        let (status, body) = self
+            .request(
+                Method::POST,
+                "/api/auth/login",
+                None,
+                Some(serde_json::json!({ "email": email, "password": password })),
+            )
+            .await;
+        (status, body["data"].clone())
+    }
+
+    /// Creates a personal access token with the given scopes and returns its plaintext.
+    pub async fn create_access_token(&self, session_token: &str, scopes: &[&str]) -> String {
+        let (status, body) = self
+            .request(
+                Method::POST,
+                "/api/users/tokens",
+                Some(session_token),
+                Some(serde_json::json!({ "name": "test", "scopes": scopes })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+        body["data"]["token"].as_str().unwrap().to_string()
+    }
+
+    /// Gives the user the built-in `admin` role. Takes effect on the next login.
+    pub async fn make_admin(&self, user_id: Uuid) {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO user_roles (user_id, role_id, created_at)
+            SELECT ?, id, datetime('now') FROM roles WHERE name = 'admin'
+            "#,
+        )
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .unwrap();
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.database.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// The `token` query parameter of the link in an emailed message.
+pub fn link_token(message: &EmailMessage) -> String {
+    let (_, rest) = message.body.split_once("token=").expect("message has no token link");
+    rest.split_whitespace().next().unwrap().to_string()
+}
+
+pub fn test_settings() -> Settings {
+    // Required by `Settings::new`. Set once, before any test reads the environment.
+    static ENV: Once = Once::new();
+    ENV.call_once(|| std::env::set_var("MFA_ENCRYPTION_KEY", "42".repeat(32)));
+
+    let mut settings = Settings::new().unwrap();
+
+    settings.argon2_memory_kib = 1024;
+    settings.argon2_iterations = 1;
+    settings.hashing_workers = 2;
+
+    let off = RateLimit::new(0, 0);
+    settings.rate_limit_login = off;
+    settings.rate_limit_register = off;
+    settings.rate_limit_refresh = off;
+    settings.rate_limit_mfa = off;
+    settings.rate_limit_email = off;
+    settings.rate_limit_token = off;
+
+    settings
+}
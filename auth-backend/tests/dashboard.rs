@@ -0,0 +1,46 @@
+//! Dashboard statistics.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use common::{TestApp, PASSWORD};
+use serde_json::json;
+
+#[tokio::test]
+async fn active_sessions_counts_sessions_that_can_still_be_refreshed() {
+    let app = TestApp::new().await;
+    let (admin_id, _) = app.register("operator@example.com").await;
+    app.make_admin(admin_id).await;
+    let (_, admin) = app.login("operator@example.com", PASSWORD).await;
+    let admin = admin["token"].as_str().unwrap().to_string();
+
+    let active_sessions = || async {
+        let (status, body) = app.request(Method::GET, "/api/admin/dashboard/stats", Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        body["data"]["active_sessions"].as_i64().unwrap()
+    };
+    let baseline = active_sessions().await;
+
+    // Two sessions of one user count twice, and refreshing one does not add another
+    app.register("member@example.com").await;
+    let (_, first) = app.login("member@example.com", PASSWORD).await;
+    let (_, second) = app.login("member@example.com", PASSWORD).await;
+    let (status, _) = app
+        .request(Method::POST, "/api/auth/refresh", None, Some(json!({ "refresh_token": first["refresh_token"] })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(active_sessions().await, baseline + 3);
+
+    // Signed out and expired sessions drop out
+    let (status, _) = app.request(Method::POST, "/api/users/sessions/revoke-others", second["token"].as_str(), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(active_sessions().await, baseline + 1);
+
+    sqlx::query("UPDATE refresh_tokens SET expires_at = ?")
+        .bind(Utc::now() - Duration::minutes(1))
+        .execute(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(active_sessions().await, 0);
+}
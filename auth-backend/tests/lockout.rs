@@ -0,0 +1,99 @@
+//! Failed password logins lock an email out for doubling periods.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use common::{TestApp, PASSWORD};
+use serde_json::{json, Value};
+
+const THRESHOLD: i64 = 3;
+const BASE_MINUTES: i64 = 15;
+const MAX_MINUTES: i64 = 60;
+
+async fn app() -> TestApp {
+    TestApp::with_settings(|settings| {
+        settings.lockout_threshold = THRESHOLD;
+        settings.lockout_base_minutes = BASE_MINUTES;
+        settings.lockout_max_minutes = MAX_MINUTES;
+    })
+    .await
+}
+
+async fn login(app: &TestApp, email: &str, password: &str) -> (StatusCode, Value) {
+    app.request(
+        Method::POST,
+        "/api/auth/login",
+        None,
+        Some(json!({ "email": email, "password": password })),
+    )
+    .await
+}
+
+/// Fails until the threshold is reached and returns the lockout's length in seconds.
+async fn lock_out(app: &TestApp, email: &str) -> i64 {
+    for _ in 0..THRESHOLD {
+        let (status, body) = login(app, email, "Wrong-Pass-123!").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", body);
+    }
+
+    // Even the right password is refused while it lasts
+    let (status, body) = login(app, email, PASSWORD).await;
+    assert_eq!(status, StatusCode::LOCKED, "{}", body);
+    assert_eq!(body["code"], "account_locked");
+    body["details"]["retry_after"].as_i64().unwrap()
+}
+
+/// Ends the current lockout without touching the counters behind the backoff.
+async fn expire_lockout(app: &TestApp, email: &str) {
+    sqlx::query("UPDATE login_lockouts SET locked_until = ? WHERE email = ?")
+        .bind(Utc::now() - Duration::seconds(1))
+        .bind(email)
+        .execute(app.pool())
+        .await
+        .unwrap();
+}
+
+fn assert_about_minutes(retry_after: i64, minutes: i64) {
+    let expected = minutes * 60;
+    assert!((expected - 5..=expected).contains(&retry_after), "{} is not about {} minutes", retry_after, minutes);
+}
+
+#[tokio::test]
+async fn consecutive_lockouts_double_up_to_the_maximum() {
+    for (email, registered) in [("lockout@example.com", true), ("nobody@example.com", false)] {
+        let app = app().await;
+        if registered {
+            app.register(email).await;
+        }
+
+        // Unknown emails go through exactly the same steps, so the responses do
+        // not reveal which addresses have accounts
+        for minutes in [BASE_MINUTES, 2 * BASE_MINUTES, 4 * BASE_MINUTES, MAX_MINUTES] {
+            assert_about_minutes(lock_out(&app, email).await, minutes);
+            expire_lockout(&app, email).await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn a_successful_login_resets_the_backoff() {
+    let app = app().await;
+    let email = "lockout@example.com";
+    app.register(email).await;
+
+    assert_about_minutes(lock_out(&app, email).await, BASE_MINUTES);
+    expire_lockout(&app, email).await;
+
+    let (status, body) = login(&app, email, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Failures below the threshold do not lock, and the next lockout starts over
+    for _ in 1..THRESHOLD {
+        login(&app, email, "Wrong-Pass-123!").await;
+    }
+    let (status, body) = login(&app, email, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_about_minutes(lock_out(&app, email).await, BASE_MINUTES);
+}
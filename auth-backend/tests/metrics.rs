@@ -0,0 +1,32 @@
+//! The Prometheus endpoint is only served to the configured scrape token.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+
+#[tokio::test]
+async fn metrics_need_the_scrape_token() {
+    let app = TestApp::with_settings(|settings| settings.metrics_token = Some("scrape-secret".to_string())).await;
+
+    let (status, _) = app.request(Method::GET, "/metrics", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.request(Method::GET, "/metrics", Some("scrape-secre"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A user's session token is not a scrape token
+    let (_, session) = app.register("metrics@example.com").await;
+    let (status, _) = app.request(Method::GET, "/metrics", Some(&session), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app.request(Method::GET, "/metrics", Some("scrape-secret"), None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn metrics_are_not_served_without_a_scrape_token() {
+    let app = TestApp::new().await;
+
+    let (status, _) = app.request(Method::GET, "/metrics", Some("anything"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
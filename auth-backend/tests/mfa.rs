@@ -0,0 +1,92 @@
+//! Second factor codes can only be used once.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::Utc;
+use common::{TestApp, PASSWORD};
+use serde_json::json;
+use totp_rs::TOTP;
+
+const EMAIL: &str = "mfa@example.com";
+
+/// Enrolls and confirms TOTP with the code for `now`. Returns the
+/// authenticator, the confirmation time and the recovery codes.
+async fn enable_totp(app: &TestApp, token: &str) -> (TOTP, u64, Vec<String>) {
+    let (status, body) = app.request(Method::POST, "/api/users/mfa/totp/enroll", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let totp = TOTP::from_url(body["data"]["otpauth_uri"].as_str().unwrap()).unwrap();
+
+    let now = Utc::now().timestamp() as u64;
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/users/mfa/totp/confirm",
+            Some(token),
+            Some(json!({ "code": totp.generate(now) })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let codes = body["data"]["codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect();
+    (totp, now, codes)
+}
+
+/// Logs in with the password and completes the second step with `code`.
+async fn verify(app: &TestApp, code: &str) -> StatusCode {
+    let (status, data) = app.login(EMAIL, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", data);
+    assert_eq!(data["mfa_required"], true);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/auth/mfa/verify",
+            None,
+            Some(json!({ "mfa_token": data["mfa_token"], "code": code })),
+        )
+        .await;
+    status
+}
+
+#[tokio::test]
+async fn a_totp_code_works_once_per_time_step() {
+    let app = TestApp::new().await;
+    let (_, token) = app.register(EMAIL).await;
+    let (totp, confirmed_at, _) = enable_totp(&app, &token).await;
+
+    // Confirming enrollment used up the current step
+    assert_eq!(verify(&app, &totp.generate(confirmed_at)).await, StatusCode::UNAUTHORIZED);
+
+    // The next step is still inside the allowed drift, once
+    let next = totp.generate(confirmed_at + 30);
+    assert_eq!(verify(&app, &next).await, StatusCode::OK);
+    assert_eq!(verify(&app, &next).await, StatusCode::UNAUTHORIZED);
+
+    // and a step before the last one used is never accepted again
+    assert_eq!(verify(&app, &totp.generate(confirmed_at)).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_recovery_code_works_once() {
+    let app = TestApp::new().await;
+    let (_, token) = app.register(EMAIL).await;
+    let (_, _, codes) = enable_totp(&app, &token).await;
+
+    assert_eq!(verify(&app, &codes[0]).await, StatusCode::OK);
+    assert_eq!(verify(&app, &codes[0]).await, StatusCode::UNAUTHORIZED);
+    // Case and separators do not make it a different code
+    assert_eq!(verify(&app, &codes[0].to_uppercase().replace('-', "")).await, StatusCode::UNAUTHORIZED);
+
+    assert_eq!(verify(&app, &codes[1]).await, StatusCode::OK);
+
+    let (status, body) = app.request(Method::GET, "/api/users/mfa/recovery-codes", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["total"], codes.len());
+    assert_eq!(body["data"]["remaining"], codes.len() - 2);
+}
@@ -0,0 +1,123 @@
+//! Organization roles: who may grant what, and the owner that must remain.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+struct Organization<'a> {
+    app: &'a TestApp,
+    id: String,
+}
+
+impl<'a> Organization<'a> {
+    async fn create(app: &'a TestApp, owner: &str) -> Self {
+        let (status, body) = app
+            .request(
+                Method::POST,
+                "/api/organizations",
+                Some(owner),
+                Some(json!({ "name": "Acme", "slug": "acme-corp" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+        Self { app, id: body["data"]["id"].as_str().unwrap().to_string() }
+    }
+
+    async fn add(&self, token: &str, email: &str, role: &str) -> (StatusCode, Value) {
+        self.app
+            .request(
+                Method::POST,
+                &format!("/api/organizations/{}/members", self.id),
+                Some(token),
+                Some(json!({ "email": email, "role": role })),
+            )
+            .await
+    }
+
+    async fn set_role(&self, token: &str, user_id: Uuid, role: &str) -> (StatusCode, Value) {
+        self.app
+            .request(
+                Method::PUT,
+                &format!("/api/organizations/{}/members/{}", self.id, user_id),
+                Some(token),
+                Some(json!({ "role": role })),
+            )
+            .await
+    }
+
+    async fn remove(&self, token: &str, user_id: Uuid) -> (StatusCode, Value) {
+        self.app
+            .request(
+                Method::DELETE,
+                &format!("/api/organizations/{}/members/{}", self.id, user_id),
+                Some(token),
+                None,
+            )
+            .await
+    }
+}
+
+fn assert_role_required(response: (StatusCode, Value), role: &str) {
+    let (status, body) = response;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["code"], "org_role_required");
+    assert_eq!(body["details"]["required"], role);
+}
+
+fn assert_status(response: (StatusCode, Value), expected: StatusCode) {
+    let (status, body) = response;
+    assert_eq!(status, expected, "{}", body);
+}
+
+#[tokio::test]
+async fn admins_cannot_grant_or_change_more_than_their_own_role() {
+    let app = TestApp::new().await;
+    let (owner_id, owner) = app.register("owner@example.com").await;
+    let (_, admin) = app.register("manager@example.com").await;
+    let (member_id, member) = app.register("member@example.com").await;
+    let (_, outsider) = app.register("outsider@example.com").await;
+    app.register("newcomer@example.com").await;
+
+    let organization = Organization::create(&app, &owner).await;
+    assert_status(organization.add(&owner, "manager@example.com", "admin").await, StatusCode::CREATED);
+    assert_status(organization.add(&owner, "member@example.com", "member").await, StatusCode::CREATED);
+
+    // Admins manage members, but owner stays out of their reach
+    assert_role_required(organization.add(&admin, "newcomer@example.com", "owner").await, "owner");
+    assert_role_required(organization.set_role(&admin, member_id, "owner").await, "owner");
+    assert_role_required(organization.set_role(&admin, owner_id, "member").await, "owner");
+    assert_role_required(organization.remove(&admin, owner_id).await, "owner");
+    assert_status(organization.set_role(&admin, member_id, "admin").await, StatusCode::OK);
+    assert_status(organization.set_role(&admin, member_id, "member").await, StatusCode::OK);
+
+    // Members manage nobody
+    assert_role_required(organization.add(&member, "newcomer@example.com", "member").await, "admin");
+    assert_role_required(organization.set_role(&member, member_id, "admin").await, "admin");
+
+    // and non-members cannot tell the organization exists
+    assert_status(organization.add(&outsider, "newcomer@example.com", "member").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn the_last_owner_cannot_leave_or_step_down() {
+    let app = TestApp::new().await;
+    let (owner_id, owner) = app.register("owner@example.com").await;
+    let (other_id, other) = app.register("co-owner@example.com").await;
+
+    let organization = Organization::create(&app, &owner).await;
+    assert_status(organization.set_role(&owner, owner_id, "admin").await, StatusCode::BAD_REQUEST);
+    assert_status(organization.remove(&owner, owner_id).await, StatusCode::BAD_REQUEST);
+
+    // With a second owner either step is allowed
+    assert_status(organization.add(&owner, "co-owner@example.com", "owner").await, StatusCode::CREATED);
+    assert_status(organization.set_role(&owner, owner_id, "admin").await, StatusCode::OK);
+
+    // which leaves the other owner as the last one
+    assert_status(organization.remove(&other, other_id).await, StatusCode::BAD_REQUEST);
+    assert_status(organization.set_role(&other, owner_id, "owner").await, StatusCode::OK);
+    assert_status(organization.remove(&other, other_id).await, StatusCode::OK);
+}
@@ -0,0 +1,301 @@
+//! Passkey registration and login against a software authenticator.
+
+mod common;
+
+use auth_backend::{
+    config::Settings,
+    models::{
+        passkey::{
+            AuthenticationCredential, AuthenticatorAssertionResponse, AuthenticatorAttestationResponse,
+            FinishPasskeyRegistrationRequest, RegistrationCredential,
+        },
+        user::{User, UserRow},
+    },
+    services::passkey_service::PasskeyService,
+    utils::webauthn::{base64url_encode, COSE_ALG_EDDSA, COSE_ALG_ES256},
+};
+use chrono::Utc;
+use ciborium::Value;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use uuid::Uuid;
+
+const RP_ID: &str = "auth.example.com";
+const ORIGIN: &str = "https://auth.example.com";
+
+const FLAG_UP_UV: u8 = 0x05;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+enum Key {
+    Es256(p256::ecdsa::SigningKey),
+    EdDsa(ed25519_dalek::SigningKey),
+}
+
+/// Holds one credential and produces the responses a browser would relay.
+struct SoftwareAuthenticator {
+    credential_id: Vec<u8>,
+    key: Key,
+    rp_id: String,
+    origin: String,
+}
+
+impl SoftwareAuthenticator {
+    fn new(alg: i64) -> Self {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let key = match alg {
+            COSE_ALG_ES256 => Key::Es256(p256::ecdsa::SigningKey::from_slice(&secret).unwrap()),
+            COSE_ALG_EDDSA => Key::EdDsa(ed25519_dalek::SigningKey::from_bytes(&secret)),
+            _ => unreachable!("unsupported test algorithm"),
+        };
+
+        let mut credential_id = vec![0u8; 16];
+        rand::thread_rng().fill_bytes(&mut credential_id);
+
+        Self { credential_id, key, rp_id: RP_ID.to_string(), origin: ORIGIN.to_string() }
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        let int = |v: i64| Value::Integer(v.into());
+        let entries = match &self.key {
+            Key::Es256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                vec![
+                    (int(1), int(2)),
+                    (int(3), int(COSE_ALG_ES256)),
+                    (int(-1), int(1)),
+                    (int(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                    (int(-3), Value::Bytes(point.y().unwrap().to_vec())),
+                ]
+            }
+            Key::EdDsa(key) => vec![
+                (int(1), int(1)),
+                (int(3), int(COSE_ALG_EDDSA)),
+                (int(-1), int(6)),
+                (int(-2), Value::Bytes(key.verifying_key().to_bytes().to_vec())),
+            ],
+        };
+
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&Value::Map(entries), &mut bytes).unwrap();
+        bytes
+    }
+
+    fn authenticator_data(&self, flags: u8, sign_count: u32) -> Vec<u8> {
+        let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    fn client_data(&self, ceremony_type: &str, challenge: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": ceremony_type,
+            "challenge": challenge,
+            "origin": self.origin,
+        }))
+        .unwrap()
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match &self.key {
+            Key::Es256(key) => {
+                use p256::ecdsa::{signature::Signer, Signature};
+                let signature: Signature = key.sign(message);
+                signature.to_der().as_bytes().to_vec()
+            }
+            Key::EdDsa(key) => {
+                use ed25519_dalek::Signer;
+                key.sign(message).to_bytes().to_vec()
+            }
+        }
+    }
+
+    fn register(&self, challenge: &str) -> FinishPasskeyRegistrationRequest {
+        let mut auth_data = self.authenticator_data(FLAG_UP_UV | FLAG_ATTESTED_CREDENTIAL_DATA, 0);
+        auth_data.extend_from_slice(&[0u8; 16]);
+        auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(&self.credential_id);
+        auth_data.extend_from_slice(&self.cose_key());
+
+        let attestation_object = Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text("none".into())),
+            (Value::Text("attStmt".into()), Value::Map(Vec::new())),
+            (Value::Text("authData".into()), Value::Bytes(auth_data)),
+        ]);
+        let mut attestation_bytes = Vec::new();
+        ciborium::ser::into_writer(&attestation_object, &mut attestation_bytes).unwrap();
+
+        FinishPasskeyRegistrationRequest {
+            name: Some("Test key".to_string()),
+            credential: RegistrationCredential {
+                id: base64url_encode(&self.credential_id),
+                raw_id: base64url_encode(&self.credential_id),
+                credential_type: "public-key".to_string(),
+                response: AuthenticatorAttestationResponse {
+                    client_data_json: base64url_encode(&self.client_data("webauthn.create", challenge)),
+                    attestation_object: base64url_encode(&attestation_bytes),
+                    transports: None,
+                },
+            },
+        }
+    }
+
+    fn assert(&self, challenge: &str, sign_count: u32) -> AuthenticationCredential {
+        let auth_data = self.authenticator_data(FLAG_UP_UV, sign_count);
+        let client_data = self.client_data("webauthn.get", challenge);
+
+        let mut signed = auth_data.clone();
+        signed.extend_from_slice(&Sha256::digest(&client_data));
+
+        AuthenticationCredential {
+            id: base64url_encode(&self.credential_id),
+            raw_id: base64url_encode(&self.credential_id),
+            credential_type: "public-key".to_string(),
+            response: AuthenticatorAssertionResponse {
+                client_data_json: base64url_encode(&client_data),
+                authenticator_data: base64url_encode(&auth_data),
+                signature: base64url_encode(&self.sign(&signed)),
+                user_handle: None,
+            },
+        }
+    }
+}
+
+async fn setup() -> (SqlitePool, Settings, User) {
+    // One connection, since every in-memory connection is its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+    let mut settings = common::test_settings();
+    settings.webauthn_rp_id = RP_ID.to_string();
+    settings.webauthn_origin = ORIGIN.to_string();
+    settings.webauthn_require_user_verification = true;
+
+    let now = Utc::now();
+    let user_row = sqlx::query_as::<_, UserRow>(
+        r#"
+        INSERT INTO users (id, email, password_hash, name, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind("passkey@example.com")
+    .bind("unused")
+    .bind("Passkey User")
+    .bind(now)
+    .bind(now)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    (pool, settings, user_row.into())
+}
+
+async fn register(service: &PasskeyService<'_>, user: &User, authenticator: &SoftwareAuthenticator) {
+    let options = service.start_registration(user).await.unwrap();
+    service
+        .finish_registration(user, authenticator.register(&options.challenge))
+        .await
+        .unwrap();
+}
+
+async fn login(
+    service: &PasskeyService<'_>,
+    user: &User,
+    authenticator: &SoftwareAuthenticator,
+    sign_count: u32,
+) -> anyhow::Result<Uuid> {
+    let options = service.start_authentication(Some(&user.email)).await?;
+    service
+        .finish_authentication(authenticator.assert(&options.challenge, sign_count))
+        .await
+}
+
+#[tokio::test]
+async fn registers_and_logs_in_with_es256_and_eddsa() {
+    let (pool, settings, user) = setup().await;
+    let service = PasskeyService::new(&pool, &settings);
+
+    for alg in [COSE_ALG_ES256, COSE_ALG_EDDSA] {
+        let authenticator = SoftwareAuthenticator::new(alg);
+        register(&service, &user, &authenticator).await;
+
+        assert_eq!(login(&service, &user, &authenticator, 1).await.unwrap(), user.id);
+        assert_eq!(login(&service, &user, &authenticator, 2).await.unwrap(), user.id);
+    }
+
+    assert_eq!(service.list_passkeys(user.id).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn rejects_a_sign_count_that_does_not_increase() {
+    let (pool, settings, user) = setup().await;
+    let service = PasskeyService::new(&pool, &settings);
+    let authenticator = SoftwareAuthenticator::new(COSE_ALG_ES256);
+    register(&service, &user, &authenticator).await;
+
+    login(&service, &user, &authenticator, 5).await.unwrap();
+
+    for sign_count in [5, 3] {
+        let error = login(&service, &user, &authenticator, sign_count).await.unwrap_err();
+        assert_eq!(error.to_string(), "Passkey counter check failed");
+    }
+}
+
+#[tokio::test]
+async fn rejects_a_foreign_origin_or_relying_party() {
+    let (pool, settings, user) = setup().await;
+    let service = PasskeyService::new(&pool, &settings);
+    let mut authenticator = SoftwareAuthenticator::new(COSE_ALG_EDDSA);
+    register(&service, &user, &authenticator).await;
+
+    authenticator.origin = "https://evil.example.com".to_string();
+    let error = login(&service, &user, &authenticator, 1).await.unwrap_err();
+    assert_eq!(error.to_string(), "Passkey origin mismatch");
+
+    authenticator.origin = ORIGIN.to_string();
+    authenticator.rp_id = "evil.example.com".to_string();
+    let error = login(&service, &user, &authenticator, 1).await.unwrap_err();
+    assert_eq!(error.to_string(), "Passkey relying party mismatch");
+
+    // The same mismatches are refused at registration
+    let other = SoftwareAuthenticator { rp_id: "evil.example.com".to_string(), ..SoftwareAuthenticator::new(COSE_ALG_ES256) };
+    let options = service.start_registration(&user).await.unwrap();
+    let error = service.finish_registration(&user, other.register(&options.challenge)).await.unwrap_err();
+    assert_eq!(error.to_string(), "Passkey relying party mismatch");
+
+    let other = SoftwareAuthenticator { origin: "https://evil.example.com".to_string(), ..SoftwareAuthenticator::new(COSE_ALG_ES256) };
+    let options = service.start_registration(&user).await.unwrap();
+    let error = service.finish_registration(&user, other.register(&options.challenge)).await.unwrap_err();
+    assert_eq!(error.to_string(), "Passkey origin mismatch");
+}
+
+#[tokio::test]
+async fn rejects_a_consumed_challenge() {
+    let (pool, settings, user) = setup().await;
+    let service = PasskeyService::new(&pool, &settings);
+    let authenticator = SoftwareAuthenticator::new(COSE_ALG_ES256);
+
+    let options = service.start_registration(&user).await.unwrap();
+    service.finish_registration(&user, authenticator.register(&options.challenge)).await.unwrap();
+    let error = service
+        .finish_registration(&user, SoftwareAuthenticator::new(COSE_ALG_ES256).register(&options.challenge))
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Invalid or expired passkey challenge");
+
+    let options = service.start_authentication(Some(&user.email)).await.unwrap();
+    service.finish_authentication(authenticator.assert(&options.challenge, 1)).await.unwrap();
+    let error = service
+        .finish_authentication(authenticator.assert(&options.challenge, 2))
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Invalid or expired passkey challenge");
+}
@@ -0,0 +1,87 @@
+//! Permission-gated routes refuse users whose roles do not grant the permission.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{TestApp, PASSWORD};
+use serde_json::{json, Value};
+
+/// Logs in again, since session tokens carry the roles held at login.
+async fn session(app: &TestApp, email: &str) -> String {
+    let (status, data) = app.login(email, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK, "{}", data);
+    data["token"].as_str().unwrap().to_string()
+}
+
+fn assert_denied(status: StatusCode, body: &Value, permission: &str) {
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["code"], "permission_denied");
+    assert_eq!(body["details"]["required"], permission);
+}
+
+#[tokio::test]
+async fn a_user_without_the_permission_is_denied() {
+    let app = TestApp::new().await;
+    let (_, token) = app.register("member@example.com").await;
+
+    for (path, permission) in [
+        ("/api/admin/audit", "audit:read"),
+        ("/api/admin/dashboard/stats", "dashboard:read"),
+        ("/api/admin/roles", "roles:manage"),
+        ("/api/admin/service-accounts", "service_accounts:manage"),
+    ] {
+        let (status, body) = app.request(Method::GET, path, Some(&token), None).await;
+        assert_denied(status, &body, permission);
+    }
+}
+
+#[tokio::test]
+async fn a_role_grants_exactly_its_permissions() {
+    let app = TestApp::new().await;
+    let (admin_id, _) = app.register("operator@example.com").await;
+    app.make_admin(admin_id).await;
+    let admin = session(&app, "operator@example.com").await;
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/admin/roles",
+            Some(&admin),
+            Some(json!({ "name": "auditor", "permissions": ["audit:read"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    let role_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (user_id, _) = app.register("auditor@example.com").await;
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            &format!("/api/admin/users/{}/roles", user_id),
+            Some(&admin),
+            Some(json!({ "roles": ["user", "auditor"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let auditor = session(&app, "auditor@example.com").await;
+    let (status, body) = app.request(Method::GET, "/api/admin/audit", Some(&auditor), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.request(Method::GET, "/api/admin/roles", Some(&auditor), None).await;
+    assert_denied(status, &body, "roles:manage");
+
+    // Taking the permission off the role takes it from its holders
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            &format!("/api/admin/roles/{}", role_id),
+            Some(&admin),
+            Some(json!({ "permissions": [] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let auditor = session(&app, "auditor@example.com").await;
+    let (status, body) = app.request(Method::GET, "/api/admin/audit", Some(&auditor), None).await;
+    assert_denied(status, &body, "audit:read");
+}
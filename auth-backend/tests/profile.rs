@@ -0,0 +1,71 @@
+//! Profile updates.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{link_token, TestApp};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn email_verified(app: &TestApp, user_id: Uuid) -> bool {
+    sqlx::query_scalar("SELECT email_verified FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap()
+}
+
+async fn mark_verified(app: &TestApp, user_id: Uuid) {
+    sqlx::query("UPDATE users SET email_verified = TRUE WHERE id = ?")
+        .bind(user_id)
+        .execute(app.pool())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn changing_the_email_requires_verifying_it_again() {
+    let app = TestApp::new().await;
+    let (user_id, token) = app.register("old@example.com").await;
+    mark_verified(&app, user_id).await;
+
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            "/api/users/profile",
+            Some(&token),
+            Some(json!({ "name": "Test User", "email": "new@example.com" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(!email_verified(&app, user_id).await);
+
+    let messages = app.mailer.sent_to("new@example.com");
+    assert_eq!(messages.len(), 1);
+
+    let (status, body) = app
+        .request(Method::POST, "/api/auth/verify-email", None, Some(json!({ "token": link_token(&messages[0]) })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(email_verified(&app, user_id).await);
+}
+
+#[tokio::test]
+async fn keeping_the_email_keeps_it_verified() {
+    let app = TestApp::new().await;
+    let (user_id, token) = app.register("same@example.com").await;
+    mark_verified(&app, user_id).await;
+    let sent_before = app.mailer.sent_to("same@example.com").len();
+
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            "/api/users/profile",
+            Some(&token),
+            Some(json!({ "name": "Renamed", "email": "same@example.com" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(email_verified(&app, user_id).await);
+    assert_eq!(app.mailer.sent_to("same@example.com").len(), sent_before);
+}
@@ -0,0 +1,51 @@
+//! Refresh token rotation and reuse detection.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{TestApp, PASSWORD};
+use serde_json::{json, Value};
+
+const EMAIL: &str = "refresh@example.com";
+
+async fn refresh(app: &TestApp, refresh_token: &Value) -> (StatusCode, Value) {
+    let (status, body) = app
+        .request(Method::POST, "/api/auth/refresh", None, Some(json!({ "refresh_token": refresh_token })))
+        .await;
+    (status, body["data"].clone())
+}
+
+#[tokio::test]
+async fn reusing_a_rotated_refresh_token_revokes_its_family() {
+    let app = TestApp::new().await;
+    app.register(EMAIL).await;
+
+    let (_, session) = app.login(EMAIL, PASSWORD).await;
+    let (_, other_session) = app.login(EMAIL, PASSWORD).await;
+
+    let (status, rotated) = refresh(&app, &session["refresh_token"]).await;
+    assert_eq!(status, StatusCode::OK, "{}", rotated);
+    assert_ne!(rotated["refresh_token"], session["refresh_token"]);
+
+    // Presenting the replaced token again looks like theft
+    let (status, _) = refresh(&app, &session["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // so the token it was rotated into, and the access token issued with it, stop working
+    let (status, _) = refresh(&app, &rotated["refresh_token"]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.request(Method::GET, "/api/auth/me", rotated["token"].as_str(), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Other sessions of the same user are not part of the family
+    let (status, body) = refresh(&app, &other_session["refresh_token"]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = app.request(Method::GET, "/api/auth/me", body["token"].as_str(), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let reuse_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_events WHERE action = 'session.refresh_token_reuse'")
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(reuse_events, 1);
+}
@@ -12,6 +12,11 @@ export const AUTH_CONFIG = {
   REFRESH_TOKEN_KEY: 'refresh_token',
   USER_KEY: 'user_data',
   REMEMBER_ME_KEY: 'remember_me',
+  // Must match the backend's AUTH_COOKIES setting. Tokens then live in HttpOnly
+  // cookies and never reach localStorage.
+  COOKIE_MODE: import.meta.env.VITE_AUTH_COOKIES === 'true',
+  CSRF_COOKIE: 'csrf_token',
+  CSRF_HEADER: 'X-CSRF-Token',
 };
 
 export const ROUTES = {
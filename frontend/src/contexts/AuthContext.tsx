@@ -96,7 +96,17 @@ export function AuthProvider({ children }: AuthProviderProps) {
         console.log('RefreshToken:', refreshToken);
         console.log('User:', user);
 
-        if (token && refreshToken && user) {
+        if (AUTH_CONFIG.COOKIE_MODE && user) {
+          // Tokens are HttpOnly cookies; an expired one is refreshed on the first 401
+          dispatch({
+            type: 'LOGIN_SUCCESS',
+            payload: {
+              user,
+              token: '',
+              refreshToken: '',
+            },
+          });
+        } else if (token && refreshToken && user) {
           const expired = isJwtExpired(token);
           console.log('Is token expired?', expired);
           if (expired) {
@@ -211,10 +221,10 @@ export function AuthProvider({ children }: AuthProviderProps) {
 
   const refreshAuthToken = async () => {
     try {
-      if (!state.refreshToken) {
+      if (!state.refreshToken && !AUTH_CONFIG.COOKIE_MODE) {
         throw new Error('No refresh token available');
       }
-      const response = await authService.refreshToken(state.refreshToken);
+      const response = await authService.refreshToken(state.refreshToken ?? '');
       storage.set(AUTH_CONFIG.TOKEN_KEY, response.token);
       storage.set(AUTH_CONFIG.REFRESH_TOKEN_KEY, response.refreshToken);
       dispatch({
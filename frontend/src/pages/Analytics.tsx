@@ -10,6 +10,7 @@ import axios from 'axios';
 interface LoginsPerDay {
   date: string;
   logins: number;
+  failed_logins: number;
 }
 
 const Analytics: React.FC = () => {
@@ -50,7 +51,7 @@ const Analytics: React.FC = () => {
             <h1 className="text-3xl font-bold text-gray-900 dark:text-white">Analytics</h1>
           </div>
           <p className="text-gray-600 dark:text-gray-400 mb-6">
-            Visualize your authentication data and trends. Here is a sample of logins over the past week.
+            Visualize your authentication data and trends. Here are successful and failed logins over the past week.
           </p>
           <div className="h-80 flex items-center justify-center bg-gradient-to-r from-primary-100 to-secondary-100 dark:from-primary-800/30 dark:to-secondary-800/30 rounded-xl">
             {loading ? (
@@ -64,7 +65,8 @@ const Analytics: React.FC = () => {
                   <XAxis dataKey="date" />
                   <YAxis allowDecimals={false} />
                   <Tooltip />
-                  <Bar dataKey="logins" fill="#6366f1" radius={[8, 8, 0, 0]} />
+                  <Bar dataKey="logins" name="Logins" fill="#6366f1" radius={[8, 8, 0, 0]} />
+                  <Bar dataKey="failed_logins" name="Failed logins" fill="#f87171" radius={[8, 8, 0, 0]} />
                 </BarChart>
               </ResponsiveContainer>
             )}
@@ -3,6 +3,7 @@ import axios from 'axios';
 export interface LoginsPerDay {
   date: string;
   logins: number;
+  failed_logins: number;
 }
 
 export const analyticsService = {
@@ -1,6 +1,6 @@
 import axios, { AxiosInstance, AxiosRequestConfig, AxiosResponse } from 'axios';
 import { API_CONFIG, AUTH_CONFIG } from '../constants/config';
-import { storage } from '../utils/storage';
+import { cookies, storage } from '../utils/storage';
 import { ApiResponse } from '../types/auth';
 import toast from 'react-hot-toast';
 
@@ -11,6 +11,8 @@ class ApiService {
     this.client = axios.create({
       baseURL: API_CONFIG.BASE_URL,
       timeout: API_CONFIG.TIMEOUT,
+      // Sends the HttpOnly session cookies in cookie mode
+      withCredentials: AUTH_CONFIG.COOKIE_MODE,
       headers: {
         'Content-Type': 'application/json',
       },
@@ -27,6 +29,11 @@ class ApiService {
         if (token) {
           config.headers.Authorization = `Bearer ${token}`;
         }
+        // Double-submit CSRF token; the backend requires it on cookie-authenticated writes
+        const csrfToken = AUTH_CONFIG.COOKIE_MODE ? cookies.get(AUTH_CONFIG.CSRF_COOKIE) : null;
+        if (csrfToken) {
+          config.headers[AUTH_CONFIG.CSRF_HEADER] = csrfToken;
+        }
         return config;
       },
       (error) => Promise.reject(error)
@@ -43,8 +50,9 @@ class ApiService {
 
           try {
             const refreshToken = storage.get<string>(AUTH_CONFIG.REFRESH_TOKEN_KEY);
-            if (refreshToken) {
-              const response = await this.refreshToken(refreshToken);
+            // In cookie mode the refresh token cookie is sent instead
+            if (refreshToken || AUTH_CONFIG.COOKIE_MODE) {
+              const response = await this.refreshToken(refreshToken ?? '');
               storage.set(AUTH_CONFIG.TOKEN_KEY, response.data.data.token);
               return this.client(originalRequest);
             }
@@ -91,7 +91,7 @@ class AuthService {
   isAuthenticated(): boolean {
     const token = storage.get<string>(AUTH_CONFIG.TOKEN_KEY);
     const user = storage.get<User>(AUTH_CONFIG.USER_KEY);
-    return !!(token && user);
+    return !!((token || AUTH_CONFIG.COOKIE_MODE) && user);
   }
 
   getStoredUser(): User | null {
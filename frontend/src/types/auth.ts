@@ -9,6 +9,8 @@ export interface User {
   created_at: string;
   updated_at: string;
   last_login?: string;
+  password_changed_at?: string;
+  must_change_password: boolean;
 }
 
 export interface UserProfile {
@@ -37,9 +39,11 @@ export interface RegisterRequest {
 
 export interface AuthResponse {
   user: User;
+  // Both tokens are absent in cookie mode, where they are HttpOnly cookies
   token: string;
   refresh_token: string;
   expires_in: number;
+  password_change_required?: 'required' | 'expired' | null;
 }
 
 export interface AuthState {
@@ -67,6 +71,7 @@ export interface DashboardStats {
   active_sessions: number;
   new_registrations_today: number;
   login_attempts_today: number;
+  failed_logins_today: number;
 }
 
 export interface ActivityItem {
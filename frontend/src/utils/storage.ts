@@ -10,6 +10,11 @@ export const storage = {
 
   set: <T>(key: string, value: T): void => {
     try {
+      // Nothing to store (e.g. tokens in cookie mode): drop any stale value
+      if (value === undefined) {
+        localStorage.removeItem(key);
+        return;
+      }
       localStorage.setItem(key, JSON.stringify(value));
     } catch (error) {
       console.error('Failed to save to localStorage:', error);
@@ -58,4 +63,15 @@ export const sessionStorage = {
       console.error('Failed to remove from sessionStorage:', error);
     }
   },
-};
\ No newline at end of file
+};
+
+// Only cookies that are not HttpOnly are visible here, such as the CSRF token
+export const cookies = {
+  get: (name: string): string | null => {
+    const prefix = `${name}=`;
+    const cookie = document.cookie
+      .split('; ')
+      .find((entry) => entry.startsWith(prefix));
+    return cookie ? decodeURIComponent(cookie.slice(prefix.length)) : null;
+  },
+};